@@ -1,22 +1,16 @@
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::{self, Rc},
-};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 #[derive(Debug, Clone)]
 struct Node {
     key: i32,
-    val: i32,
     next: Option<Rc<RefCell<Node>>>,
     prev: Option<Rc<RefCell<Node>>>,
 }
 
 impl Node {
-    fn new(key: i32, val: i32) -> Self {
+    fn new(key: i32) -> Self {
         Self {
             key,
-            val,
             next: None,
             prev: None,
         }
@@ -31,14 +25,19 @@ struct List {
 
 impl List {
     fn new() -> Self {
-        let head = Rc::new(RefCell::new(Node::new(-1, -1)));
-        let tail = Rc::new(RefCell::new(Node::new(-1, -1)));
+        let head = Rc::new(RefCell::new(Node::new(-1)));
+        let tail = Rc::new(RefCell::new(Node::new(-1)));
         head.borrow_mut().next = Some(Rc::clone(&tail));
         tail.borrow_mut().prev = Some(Rc::clone(&head));
         Self { head, tail }
     }
 
-    fn insert_from_head(&mut self, mut node: Node) -> Rc<RefCell<Node>> {
+    // Only the two sentinels are left
+    fn is_empty(&self) -> bool {
+        Rc::ptr_eq(self.head.borrow().next.as_ref().unwrap(), &self.tail)
+    }
+
+    fn insert_from_head(&mut self, node: Node) -> Rc<RefCell<Node>> {
         let node_rc = Rc::new(RefCell::new(node));
         let next = Rc::clone(self.head.borrow().next.as_ref().unwrap());
 
@@ -83,6 +82,9 @@ impl List {
     }
 
     fn remove_tail(&mut self) -> Option<Rc<RefCell<Node>>> {
+        if self.is_empty() {
+            return None;
+        }
         let tail_prev = self.tail.borrow().prev.clone()?;
         let prev_prev = tail_prev.borrow().prev.clone()?;
 
@@ -95,101 +97,430 @@ impl List {
 
 type Freq = usize;
 
-struct LFUCache {
-    capacity: i32,
+// Capacity of a cache built with `collect()`; use with_entries to pick one
+const DEFAULT_CAPACITY: i32 = 16;
+
+// Values are generic so large payloads can be borrowed instead of copied;
+// the LeetCode-style `get` with its -1 sentinel is only offered for i32.
+struct LFUCache<V = i32> {
+    // Never negative: `new` treats any capacity <= 0 as zero
+    capacity: usize,
     freq_map: HashMap<Freq, List>,
-    cache: HashMap<i32, (i32, Freq, Rc<RefCell<Node>>)>,
+    cache: HashMap<i32, (V, Freq, Rc<RefCell<Node>>)>,
     min_freq: Freq,
+    // Logical time: one tick per get_ref, get_mut, touch or put. Only
+    // aging reads it, so it stays at 0 without an aging window.
+    clock: u64,
+    // Tick of each key's latest access or insert; empty without aging
+    last_access: HashMap<i32, u64>,
+    // Set by with_aging: a key loses one unit of frequency per `window`
+    // ticks it goes untouched
+    aging_window: Option<u64>,
 }
 
 /**
  * `&self` means the method takes an immutable reference.
  * If you need a mutable reference, change it to `&mut self` instead.
  */
-impl LFUCache {
+impl<V> LFUCache<V> {
     fn new(capacity: i32) -> Self {
         Self {
-            capacity,
+            capacity: capacity.max(0) as usize,
             freq_map: HashMap::new(),
             cache: HashMap::new(),
             min_freq: 1,
+            clock: 0,
+            last_access: HashMap::new(),
+            aging_window: None,
         }
     }
 
-    fn get(&mut self, key: i32) -> i32 {
-        if self.capacity == 0 {
-            return -1;
+    // A cache of `capacity` filled by putting each pair in order, so the
+    // usual eviction applies once it is full
+    fn with_entries(capacity: i32, entries: impl IntoIterator<Item = (i32, V)>) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.extend(entries);
+        cache
+    }
+
+    // Time-aware LFU: before an access is counted, the key's frequency
+    // first decays by one per `window` ticks since it was last touched
+    // (never below 1), and eviction compares the decayed frequencies. Old
+    // popularity fades, so a key that was hot long ago can lose to one
+    // that is only warm now. Eviction scans every key in this mode.
+    #[allow(dead_code)]
+    fn with_aging(capacity: i32, window: u64) -> Self {
+        assert!(window > 0, "aging window must be at least one tick");
+        Self {
+            aging_window: Some(window),
+            ..Self::new(capacity)
         }
-        if let Some(&(val, freq, ref node_rc)) = self.cache.get(&key) {
-            let new_freq = freq + 1;
+    }
 
-            // Remove from old frequency list
-            let mut old_list = self.freq_map.get_mut(&freq).unwrap();
-            old_list.remove_node(key);
-            if old_list.head.borrow().next.is_none() {
-                self.freq_map.remove(&freq);
-                if self.min_freq == freq {
-                    self.min_freq = new_freq;
-                }
+    // Advance logical time, skipped for plain LFU where nothing reads it
+    fn tick(&mut self) {
+        if self.aging_window.is_some() {
+            self.clock += 1;
+        }
+    }
+
+    // Record an access to `key` at the current tick, again only when aging
+    fn stamp(&mut self, key: i32) {
+        if self.aging_window.is_some() {
+            self.last_access.insert(key, self.clock);
+        }
+    }
+
+    // Frequency of a cached key after aging; the stored one without it
+    fn decayed_freq(&self, key: i32) -> Freq {
+        let freq = self.cache[&key].1;
+        let Some(window) = self.aging_window else {
+            return freq;
+        };
+        let idle = self.clock - self.last_access[&key];
+        freq.saturating_sub((idle / window) as usize).max(1)
+    }
+
+    // Move a cached key from its frequency list to the next one up, or
+    // lower down if aging decayed it first
+    fn bump(&mut self, key: i32) {
+        if !self.cache.contains_key(&key) {
+            return;
+        }
+        let new_freq = self.decayed_freq(key) + 1;
+        self.stamp(key);
+        let entry = self.cache.get_mut(&key).unwrap();
+        let freq = entry.1;
+
+        // Remove from old frequency list
+        let old_list = self.freq_map.get_mut(&freq).unwrap();
+        old_list.remove_node(key);
+        if old_list.is_empty() {
+            self.freq_map.remove(&freq);
+            if self.min_freq == freq {
+                self.min_freq = new_freq;
             }
-            // Insert into new frequency list
-            let new_list = self.freq_map.entry(new_freq).or_insert_with(List::new);
-            let node = new_list.insert_from_head(Node::new(key, val));
+        }
+        self.min_freq = self.min_freq.min(new_freq);
 
-            // Update cache
-            self.cache.insert(key, (val, new_freq, node));
-            val
-        } else {
-            -1
+        // Insert into new frequency list
+        let new_list = self.freq_map.entry(new_freq).or_insert_with(List::new);
+        entry.2 = new_list.insert_from_head(Node::new(key));
+        entry.1 = new_freq;
+    }
+
+    fn get_ref(&mut self, key: i32) -> Option<&V> {
+        self.tick();
+        if self.capacity == 0 || !self.cache.contains_key(&key) {
+            return None;
         }
+        self.bump(key);
+        self.cache.get(&key).map(|(val, _, _)| val)
     }
 
-    fn put(&mut self, key: i32, value: i32) {
+    // Counts as an access, like get_ref, but lets the caller edit in place
+    #[allow(dead_code)]
+    fn get_mut(&mut self, key: i32) -> Option<&mut V> {
+        self.tick();
+        if self.capacity == 0 || !self.cache.contains_key(&key) {
+            return None;
+        }
+        self.bump(key);
+        self.cache.get_mut(&key).map(|(val, _, _)| val)
+    }
+
+    // Count an access without reading the value; false if the key is absent
+    #[allow(dead_code)]
+    fn touch(&mut self, key: i32) -> bool {
+        self.tick();
+        if self.capacity == 0 || !self.cache.contains_key(&key) {
+            return false;
+        }
+        self.bump(key);
+        true
+    }
+
+    // Look at a value without counting it as an access
+    #[allow(dead_code)]
+    fn peek(&self, key: i32) -> Option<&V> {
+        self.cache.get(&key).map(|(val, _, _)| val)
+    }
+
+    // Current access count of a key, including the put that stored it
+    #[allow(dead_code)]
+    fn frequency_of(&self, key: i32) -> Option<Freq> {
+        self.cache.get(&key).map(|&(_, freq, _)| freq)
+    }
+
+    fn put(&mut self, key: i32, value: V) {
+        self.tick();
         if self.capacity == 0 {
             return;
         }
 
-        if let Some((_, freq, node_rc)) = self.cache.get_mut(&key) {
+        if let Some(entry) = self.cache.get_mut(&key) {
             // Key exists, update value and frequency
-            let new_freq = *freq + 1;
-            node_rc.borrow_mut().val = value; //update the value of the node.
-
-            // Remove from old frequency list
-            let mut old_list = self.freq_map.get_mut(freq).unwrap();
-            old_list.remove_node(key);
-            if old_list.head.borrow().next.is_none() {
-                self.freq_map.remove(freq);
-                if self.min_freq == *freq {
-                    self.min_freq = new_freq;
-                }
-            }
-
-            // Insert into new frequency list
-            let new_list = self.freq_map.entry(new_freq).or_insert_with(List::new);
-            let node = new_list.insert_from_head(Node::new(key, value));
-
-            // Update cache
-            self.cache.insert(key, (value, new_freq, node));
+            entry.0 = value;
+            self.bump(key);
         } else {
             // Key does not exist, insert new node
-            if self.cache.len() >= self.capacity as usize {
+            if self.cache.len() >= self.capacity {
                 // Cache is full, evict least frequently used item
-                let min_list = self.freq_map.get_mut(&self.min_freq).unwrap();
-                if let Some(evicted_node) = min_list.remove_tail() {
-                    self.cache.remove(&evicted_node.borrow().key);
-                }
-                if min_list.head.borrow().next.is_none() {
-                    self.freq_map.remove(&self.min_freq);
-                }
+                self.evict();
             }
 
             // Insert new node into frequency 1 list
             let new_list = self.freq_map.entry(1).or_insert_with(List::new);
-            let node_rc = new_list.insert_from_head(Node::new(key, value));
+            let node_rc = new_list.insert_from_head(Node::new(key));
             self.cache.insert(key, (value, 1, node_rc));
+            self.stamp(key);
             self.min_freq = 1;
         }
     }
+
+    // Remove and return the current eviction victim: the least frequently
+    // used key, least recently used among ties. Afterwards min_freq is
+    // the lowest frequency still in use.
+    #[allow(dead_code)]
+    fn pop_lfu(&mut self) -> Option<(i32, V)> {
+        let evicted = self.evict();
+        self.min_freq = self.freq_map.keys().copied().min().unwrap_or(1);
+        evicted
+    }
+
+    // Callers reset min_freq afterwards, since a new key always goes in at 1
+    fn evict(&mut self) -> Option<(i32, V)> {
+        if self.cache.is_empty() {
+            return None;
+        }
+        if self.aging_window.is_none() {
+            let min_list = self.freq_map.get_mut(&self.min_freq).unwrap();
+            let key = min_list.remove_tail()?.borrow().key;
+            if min_list.is_empty() {
+                self.freq_map.remove(&self.min_freq);
+            }
+            self.last_access.remove(&key);
+            let (val, _, _) = self.cache.remove(&key)?;
+            return Some((key, val));
+        }
+
+        // Stored frequencies are stale under aging, so compare decayed
+        // ones; ties go to the key untouched the longest
+        let key = self
+            .cache
+            .keys()
+            .copied()
+            .min_by_key(|&key| (self.decayed_freq(key), self.last_access[&key]))?;
+        let (val, freq, _) = self.cache.remove(&key)?;
+        self.last_access.remove(&key);
+        let list = self.freq_map.get_mut(&freq).unwrap();
+        list.remove_node(key);
+        if list.is_empty() {
+            self.freq_map.remove(&freq);
+        }
+        Some((key, val))
+    }
+
+    // Drop every entry for which `f(key, value)` is false
+    #[allow(dead_code)]
+    fn retain(&mut self, f: impl Fn(i32, &V) -> bool) {
+        let doomed: Vec<(i32, Freq)> = self
+            .cache
+            .iter()
+            .filter(|(&key, (val, _, _))| !f(key, val))
+            .map(|(&key, &(_, freq, _))| (key, freq))
+            .collect();
+
+        for (key, freq) in doomed {
+            self.cache.remove(&key);
+            self.last_access.remove(&key);
+            let list = self.freq_map.get_mut(&freq).unwrap();
+            list.remove_node(key);
+            if list.is_empty() {
+                self.freq_map.remove(&freq);
+            }
+        }
+
+        // The old minimum may have been emptied out
+        self.min_freq = self.freq_map.keys().copied().min().unwrap_or(1);
+    }
+
+    // Cached keys in no particular order; does not touch frequencies
+    #[allow(dead_code)]
+    fn keys(&self) -> Vec<i32> {
+        self.cache.keys().copied().collect()
+    }
+
+    // Cached values in no particular order; does not touch frequencies
+    #[allow(dead_code)]
+    fn values(&self) -> Vec<&V> {
+        self.cache.values().map(|(val, _, _)| val).collect()
+    }
+}
+
+// Each pair is a put, in order, so later pairs can evict earlier ones
+impl<V> Extend<(i32, V)> for LFUCache<V> {
+    fn extend<T: IntoIterator<Item = (i32, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+// Holds at most DEFAULT_CAPACITY entries: collecting more pairs than that
+// evicts as put would. Use new and extend (or with_entries) when a
+// specific capacity is needed.
+impl<V> FromIterator<(i32, V)> for LFUCache<V> {
+    fn from_iter<T: IntoIterator<Item = (i32, V)>>(iter: T) -> Self {
+        Self::with_entries(DEFAULT_CAPACITY, iter)
+    }
+}
+
+impl LFUCache {
+    fn get(&mut self, key: i32) -> i32 {
+        self.get_or(key, -1)
+    }
+
+    // A hit counts as an access like get; a miss stores nothing
+    fn get_or(&mut self, key: i32, default: i32) -> i32 {
+        self.get_ref(key).copied().unwrap_or(default)
+    }
+}
+
+// Marks a missing neighbour in the arena lists
+#[allow(dead_code)]
+const NIL: usize = usize::MAX;
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct ArenaNode {
+    key: i32,
+    val: i32,
+    freq: Freq,
+    prev: usize,
+    next: usize,
+}
+
+// Same behaviour as LFUCache, but every node lives in one Vec and links
+// are indices into it, so there is no Rc/RefCell bookkeeping and no
+// reference cycles. Slots of evicted nodes are reused via `free`.
+#[allow(dead_code)]
+struct ArenaLFUCache {
+    capacity: usize,
+    nodes: Vec<ArenaNode>,
+    free: Vec<usize>,
+    cache: HashMap<i32, usize>,
+    // (head, tail) of the list for each frequency, most recent at the head
+    freq_lists: HashMap<Freq, (usize, usize)>,
+    min_freq: Freq,
+}
+
+#[allow(dead_code)]
+impl ArenaLFUCache {
+    fn new(capacity: i32) -> Self {
+        Self {
+            capacity: capacity.max(0) as usize,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            cache: HashMap::new(),
+            freq_lists: HashMap::new(),
+            min_freq: 1,
+        }
+    }
+
+    // Take a node out of its frequency list, dropping the list once empty
+    fn unlink(&mut self, index: usize) {
+        let ArenaNode {
+            freq, prev, next, ..
+        } = self.nodes[index];
+        let (head, tail) = self.freq_lists.get_mut(&freq).unwrap();
+        if prev == NIL {
+            *head = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next == NIL {
+            *tail = prev;
+        } else {
+            self.nodes[next].prev = prev;
+        }
+        if *head == NIL {
+            self.freq_lists.remove(&freq);
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        let freq = self.nodes[index].freq;
+        let (head, tail) = self.freq_lists.entry(freq).or_insert((NIL, NIL));
+        self.nodes[index].prev = NIL;
+        self.nodes[index].next = *head;
+        if *head == NIL {
+            *tail = index;
+        } else {
+            self.nodes[*head].prev = index;
+        }
+        *head = index;
+    }
+
+    // Move a node to the next frequency list up
+    fn bump(&mut self, index: usize) {
+        let freq = self.nodes[index].freq;
+        self.unlink(index);
+        if self.min_freq == freq && !self.freq_lists.contains_key(&freq) {
+            self.min_freq = freq + 1;
+        }
+        self.nodes[index].freq = freq + 1;
+        self.push_front(index);
+    }
+
+    fn get(&mut self, key: i32) -> i32 {
+        let Some(&index) = self.cache.get(&key) else {
+            return -1;
+        };
+        self.bump(index);
+        self.nodes[index].val
+    }
+
+    fn put(&mut self, key: i32, value: i32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(&index) = self.cache.get(&key) {
+            self.nodes[index].val = value;
+            self.bump(index);
+            return;
+        }
+
+        if self.cache.len() >= self.capacity {
+            // Evict the least recent node of the lowest frequency
+            let (_, tail) = self.freq_lists[&self.min_freq];
+            self.unlink(tail);
+            self.cache.remove(&self.nodes[tail].key);
+            self.free.push(tail);
+        }
+
+        let node = ArenaNode {
+            key,
+            val: value,
+            freq: 1,
+            prev: NIL,
+            next: NIL,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.push_front(index);
+        self.cache.insert(key, index);
+        self.min_freq = 1;
+    }
 }
 
 /**
@@ -198,7 +529,6 @@ impl LFUCache {
  * let ret_1: i32 = obj.get(key);
  * obj.put(key, value);
  */
-
 fn main() {
     let mut lfu_cache = LFUCache::new(2);
     lfu_cache.put(1, 1);
@@ -225,3 +555,367 @@ fn main() {
     println!("Cache 2: get(3): {}", lfu_cache2.get(3));
     println!("Cache 2: get(4): {}", lfu_cache2.get(4));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_operations() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        assert_eq!(lfu.get(1), 1);
+        lfu.put(3, 3); // evicts key 2
+        assert_eq!(lfu.get(2), -1);
+        assert_eq!(lfu.get(3), 3);
+        lfu.put(4, 4); // evicts key 1 (tied with 3, but least recent)
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(3), 3);
+        assert_eq!(lfu.get(4), 4);
+    }
+
+    #[test]
+    fn test_evicts_after_frequency_lists_drain() {
+        let mut lfu = LFUCache::new(3);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        lfu.put(3, 3);
+        lfu.get(1);
+        lfu.get(2);
+        lfu.get(3);
+        lfu.put(4, 4); // all at frequency 2, key 1 is the oldest
+        assert_eq!(lfu.cache.len(), 3);
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(2), 2);
+        assert_eq!(lfu.get(4), 4);
+    }
+
+    #[test]
+    fn test_get_ref_borrows_value() {
+        let mut lfu: LFUCache<Vec<u8>> = LFUCache::new(2);
+        let payload = vec![7u8; 1024];
+        let ptr = payload.as_ptr();
+        lfu.put(1, payload);
+
+        let value = lfu.get_ref(1).unwrap();
+        assert_eq!(value.as_ptr(), ptr);
+        assert_eq!(value.len(), 1024);
+        assert_eq!(lfu.cache[&1].1, 2);
+
+        lfu.get_ref(1);
+        assert_eq!(lfu.cache[&1].1, 3);
+        assert!(lfu.get_ref(2).is_none());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut lfu = LFUCache::new(3);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        lfu.put(3, 3);
+        // frequencies: 1 -> 1, 2 -> 2, 3 -> 3
+        lfu.get(2);
+        lfu.get(3);
+        lfu.get(3);
+
+        lfu.retain(|key, _| key != 1);
+        assert_eq!(lfu.cache.len(), 2);
+        assert_eq!(lfu.min_freq, 2);
+        assert!(!lfu.freq_map.contains_key(&1));
+        assert_eq!(lfu.get(1), -1);
+
+        // Warm the newcomer past 2, which is now the least frequent survivor
+        lfu.put(4, 4);
+        lfu.get(4);
+        lfu.get(4);
+        lfu.put(5, 5);
+        assert_eq!(lfu.get(2), -1);
+        assert_eq!(lfu.get(3), 3);
+        assert_eq!(lfu.get(4), 4);
+        assert_eq!(lfu.get(5), 5);
+    }
+
+    #[test]
+    fn test_keys_and_values() {
+        let mut lfu = LFUCache::new(3);
+        lfu.put(1, 10);
+        lfu.put(2, 20);
+        lfu.put(3, 30);
+
+        let mut keys = lfu.keys();
+        keys.sort();
+        assert_eq!(keys, vec![1, 2, 3]);
+
+        let mut values = lfu.values();
+        values.sort();
+        assert_eq!(values, vec![&10, &20, &30]);
+
+        // Reading them is not an access
+        assert!(lfu.cache.values().all(|&(_, freq, _)| freq == 1));
+    }
+
+    #[test]
+    fn test_get_mut_and_peek() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 10);
+        lfu.put(2, 20);
+
+        *lfu.get_mut(1).unwrap() += 5;
+        assert_eq!(lfu.peek(1), Some(&15));
+        assert_eq!(lfu.cache[&1].1, 2);
+
+        // peek is not an access
+        assert_eq!(lfu.peek(2), Some(&20));
+        assert_eq!(lfu.cache[&2].1, 1);
+        assert!(lfu.get_mut(3).is_none());
+        assert_eq!(lfu.peek(3), None);
+
+        // The bumped key survives the next eviction
+        lfu.put(3, 30);
+        assert_eq!(lfu.get(2), -1);
+        assert_eq!(lfu.get(1), 15);
+    }
+
+    #[test]
+    fn test_negative_capacity_is_zero() {
+        let mut lfu = LFUCache::new(-5);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(2), -1);
+        assert!(lfu.cache.is_empty());
+        assert!(lfu.freq_map.is_empty());
+    }
+
+    #[test]
+    fn test_frequency_of() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 1);
+        assert_eq!(lfu.frequency_of(1), Some(1));
+        lfu.get(1);
+        lfu.get(1);
+        assert_eq!(lfu.frequency_of(1), Some(3));
+        // Asking does not bump the frequency
+        assert_eq!(lfu.frequency_of(1), Some(3));
+        assert_eq!(lfu.frequency_of(2), None);
+    }
+
+    #[test]
+    fn test_arena_basic_operations() {
+        let mut lfu = ArenaLFUCache::new(2);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        assert_eq!(lfu.get(1), 1);
+        lfu.put(3, 3); // evicts key 2
+        assert_eq!(lfu.get(2), -1);
+        assert_eq!(lfu.get(3), 3);
+        lfu.put(4, 4); // evicts key 1 (tied with 3, but least recent)
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(3), 3);
+        assert_eq!(lfu.get(4), 4);
+
+        let mut empty = ArenaLFUCache::new(-1);
+        empty.put(1, 1);
+        assert_eq!(empty.get(1), -1);
+    }
+
+    #[test]
+    fn test_arena_matches_rc_cache() {
+        // Small LCG so the trace is the same on every run
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = move |bound: u64| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) % bound
+        };
+
+        for capacity in [1, 2, 5, 16] {
+            let mut rc = LFUCache::new(capacity);
+            let mut arena = ArenaLFUCache::new(capacity);
+            for step in 0..5_000 {
+                let key = next(24) as i32;
+                if next(3) == 0 {
+                    let value = next(1_000) as i32;
+                    rc.put(key, value);
+                    arena.put(key, value);
+                } else {
+                    assert_eq!(
+                        arena.get(key),
+                        rc.get(key),
+                        "capacity {} step {} key {}",
+                        capacity,
+                        step,
+                        key
+                    );
+                }
+            }
+            // Evicted slots are reused instead of growing the arena
+            assert!(arena.nodes.len() <= capacity as usize);
+        }
+    }
+
+    #[test]
+    fn test_touch() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        assert!(lfu.touch(2));
+        assert!(lfu.touch(2));
+        assert!(!lfu.touch(3));
+        assert_eq!(lfu.frequency_of(2), Some(3));
+
+        lfu.put(3, 3); // evicts the untouched key 1
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(2), 2);
+        assert_eq!(lfu.get(3), 3);
+    }
+
+    #[test]
+    fn test_no_aging_skips_clock_bookkeeping() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 1);
+        lfu.put(2, 2);
+        lfu.get(1);
+        lfu.put(3, 3);
+        assert_eq!(lfu.clock, 0);
+        assert!(lfu.last_access.is_empty());
+        assert_eq!(lfu.get(2), -1);
+        assert_eq!(lfu.get(1), 1);
+    }
+
+    #[test]
+    fn test_aging_evicts_stale_hot_key() {
+        fn run(mut lfu: LFUCache) -> LFUCache {
+            // Key 1 is hot early on, then the stream moves elsewhere
+            lfu.put(1, 1);
+            for _ in 0..4 {
+                lfu.get(1);
+            }
+            for _ in 0..10 {
+                lfu.get(99);
+            }
+            // Key 2 is only warm, but recent
+            lfu.put(2, 2);
+            lfu.get(2);
+            lfu.get(2);
+            lfu.put(3, 3);
+            lfu
+        }
+
+        // Plain LFU keeps the key with more hits overall
+        let mut plain = run(LFUCache::new(2));
+        assert_eq!(plain.get(2), -1);
+        assert_eq!(plain.get(1), 1);
+
+        let mut aging = run(LFUCache::with_aging(2, 2));
+        assert_eq!(aging.get(1), -1);
+        assert_eq!(aging.get(2), 2);
+        assert_eq!(aging.get(3), 3);
+    }
+
+    #[test]
+    fn test_aging_decays_on_read() {
+        let mut lfu = LFUCache::with_aging(1, 2);
+        lfu.put(1, 1);
+        lfu.get(1);
+        lfu.get(1);
+        assert_eq!(lfu.frequency_of(1), Some(3));
+
+        // 5 idle ticks take 2 off before the read adds 1
+        for _ in 0..4 {
+            lfu.get(99);
+        }
+        assert_eq!(lfu.get(1), 1);
+        assert_eq!(lfu.frequency_of(1), Some(2));
+        assert_eq!(lfu.min_freq, 2);
+
+        // Debt larger than the frequency bottoms out at 1
+        for _ in 0..20 {
+            lfu.get(99);
+        }
+        lfu.touch(1);
+        assert_eq!(lfu.frequency_of(1), Some(2));
+    }
+
+    #[test]
+    fn test_get_or() {
+        let mut lfu = LFUCache::new(2);
+        lfu.put(1, 10);
+
+        assert_eq!(lfu.get_or(2, 0), 0);
+        assert_eq!(lfu.peek(2), None);
+        assert_eq!(lfu.cache.len(), 1);
+
+        assert_eq!(lfu.get_or(1, 0), 10);
+        assert_eq!(lfu.frequency_of(1), Some(2));
+    }
+
+    #[test]
+    fn test_pop_lfu() {
+        let mut lfu = LFUCache::new(4);
+        assert_eq!(lfu.pop_lfu(), None);
+
+        lfu.put(1, 10);
+        lfu.put(2, 20);
+        lfu.put(3, 30);
+        lfu.put(4, 40);
+        lfu.get(1);
+        lfu.get(1);
+        lfu.get(2);
+        lfu.get(4);
+        // frequencies: 1 -> 3, 2 -> 2, 3 -> 1, 4 -> 2 (4 used after 2)
+
+        assert_eq!(lfu.pop_lfu(), Some((3, 30)));
+        assert_eq!(lfu.min_freq, 2);
+        assert_eq!(lfu.pop_lfu(), Some((2, 20)));
+        assert_eq!(lfu.pop_lfu(), Some((4, 40)));
+        assert_eq!(lfu.min_freq, 3);
+        assert_eq!(lfu.keys(), vec![1]);
+
+        // Evicting by hand leaves the cache consistent for later puts
+        lfu.put(5, 50);
+        assert_eq!(lfu.min_freq, 1);
+        assert_eq!(lfu.pop_lfu(), Some((5, 50)));
+        assert_eq!(lfu.pop_lfu(), Some((1, 10)));
+        assert_eq!(lfu.pop_lfu(), None);
+    }
+
+    #[test]
+    fn test_extend_and_collect() {
+        let pairs = (1..=5).map(|key| (key, key * 10));
+
+        // Everything sits at frequency 1, so the earliest puts go first
+        let mut lfu = LFUCache::new(3);
+        lfu.extend(pairs.clone());
+        let mut keys = lfu.keys();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![3, 4, 5]);
+        assert_eq!(lfu.get(1), -1);
+        assert_eq!(lfu.get(5), 50);
+
+        let mut built = LFUCache::with_entries(3, pairs.clone());
+        assert_eq!(built.capacity, 3);
+        let mut keys = built.keys();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![3, 4, 5]);
+        assert_eq!(built.get(2), -1);
+        assert_eq!(built.get(3), 30);
+
+        // collect() uses the default capacity, so nothing is dropped here
+        let mut collected: LFUCache = pairs.collect();
+        assert_eq!(collected.capacity, DEFAULT_CAPACITY as usize);
+        assert_eq!(collected.keys().len(), 5);
+        assert_eq!(collected.get(1), 10);
+        let crowded: LFUCache = (0..DEFAULT_CAPACITY + 2).map(|key| (key, key)).collect();
+        assert_eq!(crowded.keys().len(), DEFAULT_CAPACITY as usize);
+        assert_eq!(crowded.peek(0), None);
+        assert_eq!(crowded.peek(1), None);
+
+        // A repeated key is an update and bumps its frequency
+        let repeated: LFUCache = [(1, 1), (1, 2)].into_iter().collect();
+        assert_eq!(repeated.peek(1), Some(&2));
+        assert_eq!(repeated.frequency_of(1), Some(2));
+    }
+}
@@ -1,10 +1,15 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 /// to implement a LRUCache we will use a double-ended LinkedList to add and remove the keys and a hashmap to store the node location
 /// lets make a helper type for us to indicate a pointer to another node
 type Link = Option<Rc<RefCell<Node>>>;
 struct Node {
     key: i32,
     val: i32,
+    access_count: u64,
     prev: Link,
     next: Link,
 }
@@ -13,6 +18,7 @@ impl Node {
         Rc::new(RefCell::new(Node {
             key,
             val,
+            access_count: 0,
             prev: None,
             next: None,
         }))
@@ -23,6 +29,10 @@ struct LRUCache {
     cache: HashMap<i32, Rc<RefCell<Node>>>,
     head: Rc<RefCell<Node>>,
     tail: Rc<RefCell<Node>>,
+    /// `get` only borrows the cache, so its counters live in cells
+    hits: Cell<u64>,
+    misses: Cell<u64>,
+    evictions: u64,
 }
 /**
  * `&self` means the method takes an immutable reference.
@@ -39,6 +49,9 @@ impl LRUCache {
             cache: HashMap::new(),
             head,
             tail,
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+            evictions: 0,
         }
     }
 
@@ -61,15 +74,22 @@ impl LRUCache {
     fn get(&self, key: i32) -> i32 {
         if let Some(node) = self.cache.get(&key) {
             let val = node.as_ref().borrow().val;
+            node.as_ref().borrow_mut().access_count += 1;
             self.remove(node);
             self.insert(node);
+            self.hits.set(self.hits.get() + 1);
             val
         } else {
+            self.misses.set(self.misses.get() + 1);
             -1
         }
     }
 
     fn put(&mut self, key: i32, value: i32) {
+        // a zero-capacity cache never stores anything, so every get misses
+        if self.cap == 0 {
+            return;
+        }
         if let Some(node) = self.cache.get(&key) {
             // Update the value
             node.as_ref().borrow_mut().val = value;
@@ -77,21 +97,8 @@ impl LRUCache {
             self.insert(node);
         } else {
             // Check if we need to evict
-            if self.cache.len() == self.cap {
-                let lru_key = self
-                    .tail
-                    .as_ref()
-                    .borrow()
-                    .prev
-                    .as_ref()
-                    .unwrap()
-                    .as_ref()
-                    .borrow()
-                    .key;
-                // Remove the least recently used node
-                if let Some(lru_node) = self.cache.remove(&lru_key) {
-                    self.remove(&lru_node);
-                }
+            if self.cache.len() == self.cap && self.pop_lru().is_some() {
+                self.evictions += 1;
             }
 
             // Create and insert the new node
@@ -100,7 +107,322 @@ impl LRUCache {
             self.cache.insert(key, new_node);
         }
     }
+
+    /// `get` each key in turn, so the last hit ends up most recently used
+    /// misses come back as -1 like in `get`
+    #[allow(dead_code)]
+    fn get_many(&mut self, keys: &[i32]) -> Vec<i32> {
+        keys.iter().map(|&key| self.get(key)).collect()
+    }
+
+    /// remove and return the least recently used entry
+    /// this is a manual removal, so it does not count towards `stats` evictions
+    fn pop_lru(&mut self) -> Option<(i32, i32)> {
+        let lru = self.tail.as_ref().borrow().prev.clone().unwrap();
+        if Rc::ptr_eq(&lru, &self.head) {
+            return None;
+        }
+        let (key, val) = {
+            let lru_ref = lru.as_ref().borrow();
+            (lru_ref.key, lru_ref.val)
+        };
+        self.remove(&lru);
+        self.cache.remove(&key);
+        Some((key, val))
+    }
+
+    /// lower the capacity to `new_cap`, evicting from the least recently used end
+    /// the evicted pairs come back oldest first so the caller can persist them
+    /// a `new_cap` at or above the current capacity changes nothing
+    #[allow(dead_code)]
+    fn shrink_to(&mut self, new_cap: usize) -> Vec<(i32, i32)> {
+        if new_cap >= self.cap {
+            return Vec::new();
+        }
+        self.cap = new_cap;
+        let mut evicted = Vec::new();
+        while self.cache.len() > self.cap {
+            evicted.extend(self.pop_lru());
+        }
+        self.evictions += evicted.len() as u64;
+        evicted
+    }
+
+    /// (hits, misses, evictions) since the cache was created
+    #[allow(dead_code)]
+    fn stats(&self) -> (u64, u64, u64) {
+        (self.hits.get(), self.misses.get(), self.evictions)
+    }
+
+    #[allow(dead_code)]
+    fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// number of successful `get` calls per key, sorted by key
+    /// this is only bookkeeping and has no effect on eviction order
+    #[allow(dead_code)]
+    fn access_counts(&self) -> Vec<(i32, u64)> {
+        let mut counts: Vec<(i32, u64)> = self
+            .cache
+            .iter()
+            .map(|(&key, node)| (key, node.as_ref().borrow().access_count))
+            .collect();
+        counts.sort_unstable();
+        counts
+    }
+
+    /// borrow the value under `key`, storing `default` first if it is missing
+    /// the value lives inside a `RefCell` node, so the borrow comes back as a `Ref`
+    /// counts as a hit or a miss in `stats` exactly like `get`
+    /// only a zero-capacity cache, which cannot store anything, returns `None`
+    #[allow(dead_code)]
+    fn get_or_insert(&mut self, key: i32, default: i32) -> Option<Ref<'_, i32>> {
+        // `get` promotes a present key and does the hit/miss bookkeeping
+        self.get(key);
+        if !self.cache.contains_key(&key) {
+            self.put(key, default);
+        }
+        let node = self.cache.get(&key)?;
+        Some(Ref::map(node.as_ref().borrow(), |node| &node.val))
+    }
+
+    #[allow(dead_code)]
+    fn entry(&mut self, key: i32) -> Entry<'_> {
+        Entry { cache: self, key }
+    }
+
+    /// drop every entry for which `f(key, value)` is false
+    /// survivors keep their relative recency order
+    #[allow(dead_code)]
+    fn retain(&mut self, f: impl Fn(i32, i32) -> bool) {
+        let mut current = self.head.as_ref().borrow().next.clone().unwrap();
+        while !Rc::ptr_eq(&current, &self.tail) {
+            let next = current.as_ref().borrow().next.clone().unwrap();
+            let (key, val) = {
+                let node = current.as_ref().borrow();
+                (node.key, node.val)
+            };
+            if !f(key, val) {
+                self.remove(&current);
+                self.cache.remove(&key);
+            }
+            current = next;
+        }
+    }
+}
+
+/// a view into a single key of the cache, like `HashMap::entry`
+/// every access through it promotes the key to most recently used
+#[allow(dead_code)]
+struct Entry<'a> {
+    cache: &'a mut LRUCache,
+    key: i32,
+}
+
+#[allow(dead_code)]
+impl Entry<'_> {
+    /// not counted in `stats`, so `and_modify(..).or_insert(..)` is one access
+    fn and_modify(self, f: impl FnOnce(&mut i32)) -> Self {
+        if let Some(node) = self.cache.cache.get(&self.key) {
+            f(&mut node.as_ref().borrow_mut().val);
+            self.cache.remove(node);
+            self.cache.insert(node);
+        }
+        self
+    }
+
+    /// returns the value now stored under the key, counted in `stats` like `get`
+    /// a zero-capacity cache stores nothing and hands `default` straight back
+    fn or_insert(self, default: i32) -> i32 {
+        self.cache
+            .get_or_insert(self.key, default)
+            .map_or(default, |val| *val)
+    }
+}
+/// slots of the two sentinels in every `ArenaLruCache`
+#[allow(dead_code)]
+const HEAD: usize = 0;
+#[allow(dead_code)]
+const TAIL: usize = 1;
+
+#[allow(dead_code)]
+struct ArenaNode {
+    key: i32,
+    val: i32,
+    prev: usize,
+    next: usize,
+}
+
+/// the same cache with every node in one vec and links stored as indices
+/// nothing is reference counted, so there are no cycles and `get` clones nothing
+/// slots of evicted nodes go on a free list and are reused by later puts
+#[allow(dead_code)]
+struct ArenaLruCache {
+    cap: usize,
+    cache: HashMap<i32, usize>,
+    nodes: Vec<ArenaNode>,
+    free: Vec<usize>,
+}
+
+#[allow(dead_code)]
+impl ArenaLruCache {
+    fn new(capacity: i32) -> Self {
+        Self::with_capacity(capacity as usize)
+    }
+
+    /// `new` with the capacity already as a `usize`, for callers that never had an `i32`
+    fn with_capacity(cap: usize) -> Self {
+        let sentinel = |prev, next| ArenaNode {
+            key: -1,
+            val: -1,
+            prev,
+            next,
+        };
+        Self {
+            cap,
+            cache: HashMap::new(),
+            nodes: vec![sentinel(HEAD, TAIL), sentinel(HEAD, TAIL)],
+            free: Vec::new(),
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        let (prev, next) = (self.nodes[index].prev, self.nodes[index].next);
+        self.nodes[prev].next = next;
+        self.nodes[next].prev = prev;
+    }
+
+    fn insert(&mut self, index: usize) {
+        let first = self.nodes[HEAD].next;
+        self.nodes[index].prev = HEAD;
+        self.nodes[index].next = first;
+        self.nodes[first].prev = index;
+        self.nodes[HEAD].next = index;
+    }
+
+    fn get(&mut self, key: i32) -> i32 {
+        let Some(&index) = self.cache.get(&key) else {
+            return -1;
+        };
+        self.remove(index);
+        self.insert(index);
+        self.nodes[index].val
+    }
+
+    fn put(&mut self, key: i32, value: i32) {
+        if self.cap == 0 {
+            return;
+        }
+        if let Some(&index) = self.cache.get(&key) {
+            self.nodes[index].val = value;
+            self.remove(index);
+            self.insert(index);
+            return;
+        }
+
+        if self.cache.len() == self.cap {
+            self.pop_lru();
+        }
+
+        let node = ArenaNode {
+            key,
+            val: value,
+            prev: HEAD,
+            next: HEAD,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.insert(index);
+        self.cache.insert(key, index);
+    }
+
+    fn contains(&self, key: i32) -> bool {
+        self.cache.contains_key(&key)
+    }
+
+    /// remove `key` and hand back its value, wherever it sits in the order
+    fn take(&mut self, key: i32) -> Option<i32> {
+        let index = self.cache.remove(&key)?;
+        self.remove(index);
+        self.free.push(index);
+        Some(self.nodes[index].val)
+    }
+
+    /// remove and return the least recently used entry
+    fn pop_lru(&mut self) -> Option<(i32, i32)> {
+        let lru = self.nodes[TAIL].prev;
+        if lru == HEAD {
+            return None;
+        }
+        let key = self.nodes[lru].key;
+        self.take(key).map(|val| (key, val))
+    }
 }
+
+/// segmented LRU: new keys start out in `probation` and only move to `protected`
+/// once they are hit again, so a scan of one-off keys can only churn probation
+/// when protected is full its least recently used entry drops back into probation
+#[allow(dead_code)]
+struct SegmentedLruCache {
+    probation: ArenaLruCache,
+    protected: ArenaLruCache,
+}
+
+#[allow(dead_code)]
+impl SegmentedLruCache {
+    fn new(probation: usize, protected: usize) -> Self {
+        Self {
+            probation: ArenaLruCache::with_capacity(probation),
+            protected: ArenaLruCache::with_capacity(protected),
+        }
+    }
+
+    fn get(&mut self, key: i32) -> i32 {
+        if self.protected.contains(key) {
+            return self.protected.get(key);
+        }
+        if !self.probation.contains(key) {
+            return -1;
+        }
+        if self.protected.cap == 0 {
+            return self.probation.get(key);
+        }
+        let val = self.probation.take(key).unwrap();
+        self.promote(key, val);
+        val
+    }
+
+    /// overwriting a key counts as a hit, so a probation key gets promoted
+    fn put(&mut self, key: i32, value: i32) {
+        if self.protected.contains(key) {
+            self.protected.put(key, value);
+        } else if self.probation.contains(key) && self.protected.cap > 0 {
+            self.probation.take(key);
+            self.promote(key, value);
+        } else {
+            self.probation.put(key, value);
+        }
+    }
+
+    /// the caller has just taken `key` out of probation, so a demoted entry always fits
+    fn promote(&mut self, key: i32, value: i32) {
+        if self.protected.cache.len() == self.protected.cap {
+            let (demoted_key, demoted_val) = self.protected.pop_lru().unwrap();
+            self.probation.put(demoted_key, demoted_val);
+        }
+        self.protected.put(key, value);
+    }
+}
+
 /**
  * Your LRUCache object will be instantiated and called as such:
  * let obj = LRUCache::new(capacity);
@@ -126,6 +448,18 @@ fn main() {
 mod tests {
     use super::*;
 
+    // keys from most to least recently used
+    fn recency(lru: &LRUCache) -> Vec<i32> {
+        let mut keys = Vec::new();
+        let mut current = lru.head.as_ref().borrow().next.clone().unwrap();
+        while !Rc::ptr_eq(&current, &lru.tail) {
+            keys.push(current.as_ref().borrow().key);
+            let next = current.as_ref().borrow().next.clone().unwrap();
+            current = next;
+        }
+        keys
+    }
+
     #[test]
     fn test_basic_operations() {
         let mut lru = LRUCache::new(2);
@@ -162,4 +496,304 @@ mod tests {
         assert_eq!(lru.get(1), -1);
         assert_eq!(lru.get(2), 2);
     }
+
+    #[test]
+    fn test_capacity_zero() {
+        let mut lru = LRUCache::new(0);
+        lru.put(1, 1);
+        assert_eq!(lru.get(1), -1);
+        assert_eq!(lru.len(), 0);
+        assert_eq!(recency(&lru), vec![]);
+    }
+
+    #[test]
+    fn test_access_counts() {
+        let mut lru = LRUCache::new(3);
+        lru.put(1, 1);
+        lru.put(2, 2);
+        lru.put(3, 3);
+        lru.get(1);
+        lru.get(1);
+        lru.get(1);
+        lru.get(2);
+        lru.get(4); // miss, not counted
+        assert_eq!(lru.access_counts(), vec![(1, 3), (2, 1), (3, 0)]);
+
+        // counting must not change LRU order: 3 is still the least recently used
+        lru.put(4, 4);
+        assert_eq!(lru.get(3), -1);
+        assert_eq!(lru.access_counts(), vec![(1, 3), (2, 1), (4, 0)]);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut lru = LRUCache::new(2);
+        lru.put(5, 1);
+        lru.put(6, 6);
+
+        // modify path: existing key is updated and promoted
+        let val = lru.entry(5).and_modify(|v| *v += 1).or_insert(10);
+        assert_eq!(val, 2);
+        assert_eq!(recency(&lru), vec![5, 6]);
+
+        // insert path: missing key gets the default and evicts the LRU
+        let val = lru.entry(7).and_modify(|v| *v += 1).or_insert(10);
+        assert_eq!(val, 10);
+        assert_eq!(recency(&lru), vec![7, 5]);
+        assert_eq!(lru.get(6), -1);
+        assert_eq!(lru.get(5), 2);
+        assert_eq!(lru.get(7), 10);
+    }
+
+    #[test]
+    fn test_entry_counts_like_get() {
+        let mut lru = LRUCache::new(2);
+        lru.put(1, 1);
+        lru.entry(1).and_modify(|v| *v += 1).or_insert(0); // hit
+        lru.entry(2).and_modify(|v| *v += 1).or_insert(0); // miss
+        lru.entry(2).or_insert(5); // hit
+        assert_eq!(lru.stats(), (2, 1, 0));
+        assert_eq!(lru.access_counts(), vec![(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_entry_zero_capacity() {
+        let mut lru = LRUCache::new(0);
+        assert_eq!(lru.entry(1).and_modify(|v| *v += 1).or_insert(7), 7);
+        assert_eq!(lru.len(), 0);
+        assert_eq!(lru.get(1), -1);
+        assert_eq!(lru.stats(), (0, 2, 0));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut lru = LRUCache::new(5);
+        for key in 1..=5 {
+            lru.put(key, key);
+        }
+        lru.get(2);
+        assert_eq!(recency(&lru), vec![2, 5, 4, 3, 1]);
+
+        lru.retain(|_, val| val > 2);
+        assert_eq!(recency(&lru), vec![5, 4, 3]);
+        assert_eq!(lru.get(1), -1);
+        assert_eq!(lru.get(2), -1);
+
+        // freed slots can be reused without evicting survivors
+        lru.put(6, 6);
+        lru.put(7, 7);
+        assert_eq!(recency(&lru), vec![7, 6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut lru = LRUCache::new(2);
+        assert_eq!(lru.stats(), (0, 0, 0));
+
+        lru.put(1, 1);
+        lru.put(2, 2);
+        lru.get(1); // hit
+        lru.put(3, 3); // evicts 2
+        lru.get(2); // miss
+        lru.put(1, 10); // update, no eviction
+        lru.put(4, 4); // evicts 3
+        lru.get(3); // miss
+        lru.get(1); // hit
+        lru.get(4); // hit
+        assert_eq!(lru.stats(), (3, 2, 2));
+    }
+
+    #[test]
+    fn test_get_or_insert() {
+        let mut lru = LRUCache::new(2);
+        lru.put(1, 1);
+        lru.put(2, 2);
+
+        // missing key: the default is stored, the LRU key evicted
+        assert_eq!(*lru.get_or_insert(3, 30).unwrap(), 30);
+        assert_eq!(recency(&lru), vec![3, 2]);
+
+        // present key: the stored value wins and the key is promoted
+        assert_eq!(*lru.get_or_insert(2, 99).unwrap(), 2);
+        assert_eq!(recency(&lru), vec![2, 3]);
+        assert_eq!(lru.get(1), -1);
+    }
+
+    #[test]
+    fn test_get_or_insert_counts_like_get() {
+        let mut lru = LRUCache::new(2);
+        lru.put(1, 10);
+
+        assert_eq!(*lru.get_or_insert(1, 0).unwrap(), 10);
+        assert_eq!(*lru.get_or_insert(2, 20).unwrap(), 20);
+        assert_eq!(*lru.get_or_insert(2, 0).unwrap(), 20);
+        // a stored -1 is still a hit, not a missing key
+        lru.put(1, -1);
+        assert_eq!(*lru.get_or_insert(1, 5).unwrap(), -1);
+
+        assert_eq!(lru.stats(), (3, 1, 0));
+        assert_eq!(lru.access_counts(), vec![(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn test_get_or_insert_zero_capacity() {
+        let mut lru = LRUCache::new(0);
+        assert!(lru.get_or_insert(1, 1).is_none());
+        assert_eq!(lru.len(), 0);
+        assert_eq!(lru.stats(), (0, 1, 0));
+    }
+
+    #[test]
+    fn test_arena_matches_rc_cache() {
+        // small lcg so the trace is the same on every run
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move |bound: u64| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) % bound
+        };
+
+        for capacity in [0, 1, 3, 10] {
+            let mut rc = LRUCache::new(capacity);
+            let mut arena = ArenaLruCache::new(capacity);
+            for step in 0..5_000 {
+                let key = next(16) as i32;
+                if next(2) == 0 {
+                    let value = next(1_000) as i32;
+                    rc.put(key, value);
+                    arena.put(key, value);
+                } else {
+                    assert_eq!(
+                        arena.get(key),
+                        rc.get(key),
+                        "capacity {} step {} key {}",
+                        capacity,
+                        step,
+                        key
+                    );
+                }
+            }
+            // the two sentinels plus at most `capacity` live slots
+            assert!(arena.nodes.len() <= capacity as usize + 2);
+        }
+    }
+
+    #[test]
+    fn test_get_many() {
+        let mut lru = LRUCache::new(4);
+        for key in 1..=4 {
+            lru.put(key, key * 10);
+        }
+        assert_eq!(recency(&lru), vec![4, 3, 2, 1]);
+
+        assert_eq!(lru.get_many(&[2, 5, 1, 3]), vec![20, -1, 10, 30]);
+        assert_eq!(recency(&lru), vec![3, 1, 2, 4]);
+        assert_eq!(lru.stats(), (3, 1, 0));
+
+        // 4 is now the least recently used and goes first
+        lru.put(6, 60);
+        assert_eq!(recency(&lru), vec![6, 3, 1, 2]);
+        assert_eq!(lru.get_many(&[]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_segmented_lru_resists_scans() {
+        let mut slru = SegmentedLruCache::new(2, 2);
+        slru.put(1, 10);
+        assert_eq!(slru.get(1), 10); // second access promotes key 1
+        assert!(slru.protected.contains(1));
+
+        for key in 100..200 {
+            slru.put(key, key);
+        }
+        assert_eq!(slru.get(1), 10);
+        assert_eq!(slru.get(198), 198);
+        assert_eq!(slru.get(100), -1);
+
+        // a plain LRU of the same total size loses key 1 to the scan
+        let mut lru = LRUCache::new(4);
+        lru.put(1, 10);
+        lru.get(1);
+        for key in 100..200 {
+            lru.put(key, key);
+        }
+        assert_eq!(lru.get(1), -1);
+    }
+
+    #[test]
+    fn test_segmented_lru_demotes_into_probation() {
+        let mut slru = SegmentedLruCache::new(2, 1);
+        slru.put(1, 1);
+        slru.put(2, 2);
+        slru.get(1);
+        // protected only holds one key, so promoting 2 sends 1 back to probation
+        slru.put(2, 20);
+        assert!(slru.protected.contains(2));
+        assert!(slru.probation.contains(1));
+        assert_eq!(slru.get(2), 20);
+
+        // 1 was demoted before 3 arrived, so a full probation evicts 1 first
+        slru.put(3, 3);
+        slru.put(4, 4);
+        assert_eq!(slru.get(1), -1);
+        assert_eq!(slru.get(3), 3);
+    }
+
+    #[test]
+    fn test_segmented_lru_keeps_large_capacities() {
+        // 2^32 would wrap to 0 as an i32 and cache nothing
+        let big = 1usize << 32;
+        let mut slru = SegmentedLruCache::new(big, big);
+        assert_eq!(slru.probation.cap, big);
+        slru.put(1, 1);
+        assert_eq!(slru.get(1), 1);
+        assert_eq!(slru.get(1), 1);
+        assert!(slru.protected.contains(1));
+    }
+
+    #[test]
+    fn test_pop_lru() {
+        let mut lru = LRUCache::new(3);
+        assert_eq!(lru.pop_lru(), None);
+
+        lru.put(1, 10);
+        lru.put(2, 20);
+        lru.put(3, 30);
+        lru.get(2);
+
+        assert_eq!(lru.pop_lru(), Some((1, 10)));
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(1), -1);
+        assert_eq!(recency(&lru), vec![2, 3]);
+
+        assert_eq!(lru.pop_lru(), Some((3, 30)));
+        assert_eq!(lru.pop_lru(), Some((2, 20)));
+        assert_eq!(lru.pop_lru(), None);
+        assert_eq!(lru.len(), 0);
+        assert_eq!(lru.stats().2, 0);
+    }
+
+    #[test]
+    fn test_shrink_to() {
+        let mut lru = LRUCache::new(4);
+        for key in 1..=4 {
+            lru.put(key, key * 10);
+        }
+        lru.get(1);
+        // recency is now 1, 4, 3, 2
+
+        assert_eq!(lru.shrink_to(2), vec![(2, 20), (3, 30)]);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(recency(&lru), vec![1, 4]);
+        assert_eq!(lru.stats().2, 2);
+
+        // the lower capacity sticks for later puts
+        lru.put(5, 50);
+        assert_eq!(recency(&lru), vec![5, 1]);
+
+        assert!(lru.shrink_to(8).is_empty());
+        lru.put(6, 60);
+        assert_eq!(lru.len(), 2);
+    }
 }
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 /// Rust does not have a built-in stack data structure, but we can implement one using a vector.
 struct Stack<T> {
     stack: Vec<T>,
@@ -15,16 +17,38 @@ impl<T> Stack<T> {
     fn top(&self) -> Option<&T> {
         self.stack.last()
     }
+    #[allow(dead_code)]
     fn is_empty(&self) -> bool {
         self.stack.is_empty()
     }
+    #[allow(dead_code)]
+    fn pop_or(&mut self, default: T) -> T {
+        self.stack.pop().unwrap_or(default)
+    }
+    #[allow(dead_code)]
+    fn top_or<'a>(&'a self, default: &'a T) -> &'a T {
+        self.stack.last().unwrap_or(default)
+    }
+    // Pops from the top while `pred` holds, so the result is top first
+    #[allow(dead_code)]
+    fn drain_while(&mut self, pred: impl Fn(&T) -> bool) -> Vec<T> {
+        let keep = self
+            .stack
+            .iter()
+            .rposition(|value| !pred(value))
+            .map_or(0, |i| i + 1);
+        self.stack.drain(keep..).rev().collect()
+    }
 }
 
 // We'll remove the get_min method from Stack as it only works for i32
 // and instead implement it inside the MinStack
 
+// Every entry carries the minimum as of its push, so popping just exposes
+// the previous snapshot: repeated minima need no counting and get_min
+// stays O(1) however many copies are stacked
 struct MinStack {
-    stack: Stack<(i32, i32)>, // (value, current_min)
+    stack: Stack<(i32, i32, Option<i32>)>, // (value, current_min, current_second_min)
 }
 
 /**
@@ -39,12 +63,20 @@ impl MinStack {
     }
 
     fn push(&mut self, val: i32) {
-        let min = if self.stack.is_empty() {
-            val
-        } else {
-            std::cmp::min(val, self.get_min())
+        let (min, second_min) = match self.stack.top() {
+            None => (val, None),
+            Some(&(_, min, second_min)) => {
+                if val < min {
+                    (val, Some(min))
+                } else if val == min {
+                    // Only distinct values count as the second smallest
+                    (min, second_min)
+                } else {
+                    (min, Some(second_min.map_or(val, |second| second.min(val))))
+                }
+            }
         };
-        self.stack.push((val, min));
+        self.stack.push((val, min, second_min));
     }
 
     fn pop(&mut self) {
@@ -53,17 +85,132 @@ impl MinStack {
 
     fn top(&self) -> i32 {
         match self.stack.top() {
-            Some((val, _)) => *val,
+            Some((val, _, _)) => *val,
             None => -1,
         }
     }
 
     fn get_min(&self) -> i32 {
         match self.stack.top() {
-            Some((_, min)) => *min,
+            Some((_, min, _)) => *min,
             None => -1,
         }
     }
+
+    // Second smallest distinct value, if the stack holds at least two
+    #[allow(dead_code)]
+    fn second_min(&self) -> Option<i32> {
+        self.stack.top().and_then(|&(_, _, second_min)| second_min)
+    }
+
+    // Values bottom-to-top; the running minima are not stored since
+    // from_vec can recompute them
+    #[allow(dead_code)]
+    fn to_vec(&self) -> Vec<i32> {
+        self.stack.stack.iter().map(|&(val, _, _)| val).collect()
+    }
+
+    #[allow(dead_code)]
+    fn from_vec(values: Vec<i32>) -> MinStack {
+        let mut min_stack = MinStack::new();
+        for val in values {
+            min_stack.push(val);
+        }
+        min_stack
+    }
+
+    // Pushes other's values bottom to top, so its top ends up on top;
+    // other's stored minima only cover other, so they are recomputed
+    #[allow(dead_code)]
+    fn append(&mut self, other: MinStack) {
+        for (val, _, _) in other.stack.stack {
+            self.push(val);
+        }
+    }
+
+    // The minimum as it stood after each push, bottom to top
+    #[allow(dead_code)]
+    fn min_history(&self) -> Vec<i32> {
+        self.stack.stack.iter().map(|&(_, min, _)| min).collect()
+    }
+
+    // Bottom-to-top indices of every value equal to the current minimum
+    #[allow(dead_code)]
+    fn min_positions(&self) -> Vec<usize> {
+        let Some(&(_, min, _)) = self.stack.top() else {
+            return Vec::new();
+        };
+        self.stack
+            .stack
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(val, _, _))| val == min)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // New stack with `f` applied to every value. The minima are rebuilt by
+    // pushing again, since `f` need not preserve order.
+    #[allow(dead_code)]
+    fn map(&self, f: impl Fn(i32) -> i32) -> MinStack {
+        MinStack::from_vec(self.to_vec().into_iter().map(f).collect())
+    }
+
+    // k-th smallest value on the stack (1-indexed). Unlike get_min this is
+    // O(n): it copies the values and partially selects the k-th one.
+    #[allow(dead_code)]
+    fn kth_min(&self, k: usize) -> Option<i32> {
+        if k == 0 || k > self.stack.stack.len() {
+            return None;
+        }
+        let mut values: Vec<i32> = self.stack.stack.iter().map(|&(val, _, _)| val).collect();
+        let (_, kth, _) = values.select_nth_unstable(k - 1);
+        Some(*kth)
+    }
+}
+
+// Like MinStack, but "smallest" is decided by a comparator closure and the
+// values can be any type. Each entry remembers the position of the smallest
+// value at or below it, so T never has to be cloned.
+#[allow(dead_code)]
+struct MinStackBy<T> {
+    stack: Stack<(T, usize)>, // (value, index of current min)
+    cmp: Comparator<T>,
+}
+
+#[allow(dead_code)]
+type Comparator<T> = Box<dyn Fn(&T, &T) -> Ordering>;
+
+#[allow(dead_code)]
+impl<T> MinStackBy<T> {
+    fn new(cmp: impl Fn(&T, &T) -> Ordering + 'static) -> Self {
+        MinStackBy {
+            stack: Stack::new(),
+            cmp: Box::new(cmp),
+        }
+    }
+
+    fn push(&mut self, val: T) {
+        let min_index = match self.get_min() {
+            // Ties keep the older value as the min
+            Some(min) if (self.cmp)(&val, min) != Ordering::Less => self.stack.top().unwrap().1,
+            _ => self.stack.stack.len(),
+        };
+        self.stack.push((val, min_index));
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.stack.pop().map(|(val, _)| val)
+    }
+
+    fn top(&self) -> Option<&T> {
+        self.stack.top().map(|(val, _)| val)
+    }
+
+    fn get_min(&self) -> Option<&T> {
+        let &(_, min_index) = self.stack.top()?;
+        Some(&self.stack.stack[min_index].0)
+    }
 }
 
 /**
@@ -165,4 +312,216 @@ mod tests {
         min_stack.pop(); // Remove 3
         assert_eq!(min_stack.get_min(), 5);
     }
+
+    #[test]
+    fn test_kth_min() {
+        let mut min_stack = MinStack::new();
+        for val in [5, 1, 3, 2, 4] {
+            min_stack.push(val);
+        }
+        assert_eq!(min_stack.kth_min(1), Some(1));
+        assert_eq!(min_stack.kth_min(3), Some(3));
+        assert_eq!(min_stack.kth_min(5), Some(5));
+        assert_eq!(min_stack.kth_min(6), None);
+        assert_eq!(min_stack.kth_min(0), None);
+
+        min_stack.pop(); // Remove 4
+        assert_eq!(min_stack.kth_min(4), Some(5));
+    }
+
+    #[test]
+    fn test_min_stack_by_shortest_string() {
+        let mut stack =
+            MinStackBy::new(|a: &String, b: &String| a.chars().count().cmp(&b.chars().count()));
+        assert_eq!(stack.get_min(), None);
+        assert_eq!(stack.top(), None);
+
+        stack.push("banana".to_string());
+        assert_eq!(stack.get_min().unwrap(), "banana");
+
+        stack.push("fig".to_string());
+        assert_eq!(stack.get_min().unwrap(), "fig");
+
+        stack.push("kiwi".to_string());
+        stack.push("pea".to_string()); // same length as fig, fig stays the min
+        assert_eq!(stack.top().unwrap(), "pea");
+        assert_eq!(stack.get_min().unwrap(), "fig");
+
+        stack.push("é".to_string()); // one character, two bytes
+        assert_eq!(stack.get_min().unwrap(), "é");
+
+        assert_eq!(stack.pop().unwrap(), "é");
+        assert_eq!(stack.get_min().unwrap(), "fig");
+        stack.pop();
+        stack.pop();
+        assert_eq!(stack.get_min().unwrap(), "fig");
+        stack.pop();
+        assert_eq!(stack.get_min().unwrap(), "banana");
+        stack.pop();
+        assert_eq!(stack.get_min(), None);
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_to_vec_from_vec_round_trip() {
+        let mut original = MinStack::new();
+        for val in [4, 2, 7, 1, 3] {
+            original.push(val);
+        }
+        let snapshot = original.to_vec();
+        assert_eq!(snapshot, vec![4, 2, 7, 1, 3]);
+
+        let mut restored = MinStack::from_vec(snapshot);
+        assert_eq!(restored.to_vec(), original.to_vec());
+        for _ in 0..5 {
+            assert_eq!(restored.top(), original.top());
+            assert_eq!(restored.get_min(), original.get_min());
+            restored.pop();
+            original.pop();
+        }
+        assert_eq!(restored.get_min(), -1);
+        assert!(MinStack::from_vec(Vec::new()).to_vec().is_empty());
+    }
+
+    #[test]
+    fn test_stack_pop_or_top_or() {
+        let mut stack: Stack<i32> = Stack::new();
+        assert_eq!(stack.pop_or(-1), -1);
+        assert_eq!(*stack.top_or(&0), 0);
+
+        stack.push(7);
+        stack.push(9);
+        assert_eq!(*stack.top_or(&0), 9);
+        assert_eq!(stack.pop_or(-1), 9);
+        assert_eq!(stack.pop_or(-1), 7);
+        assert_eq!(stack.pop_or(-1), -1);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_map_recomputes_minima() {
+        let mut min_stack = MinStack::new();
+        for val in [1, 2, 3] {
+            min_stack.push(val);
+        }
+
+        let mut negated = min_stack.map(|val| -val);
+        assert_eq!(negated.to_vec(), vec![-1, -2, -3]);
+        assert_eq!(negated.get_min(), -3);
+        negated.pop();
+        assert_eq!(negated.get_min(), -2);
+
+        // The source stack is untouched
+        assert_eq!(min_stack.get_min(), 1);
+        assert_eq!(min_stack.top(), 3);
+    }
+
+    #[test]
+    fn test_min_history() {
+        let mut min_stack = MinStack::new();
+        assert!(min_stack.min_history().is_empty());
+        for val in [5, 3, 7, 1] {
+            min_stack.push(val);
+        }
+        assert_eq!(min_stack.min_history(), vec![5, 3, 3, 1]);
+
+        min_stack.pop();
+        assert_eq!(min_stack.min_history(), vec![5, 3, 3]);
+    }
+
+    #[test]
+    fn test_second_min() {
+        let mut min_stack = MinStack::new();
+        assert_eq!(min_stack.second_min(), None);
+        min_stack.push(5);
+        assert_eq!(min_stack.second_min(), None);
+        min_stack.push(3);
+        min_stack.push(3);
+        min_stack.push(1);
+        assert_eq!(min_stack.get_min(), 1);
+        assert_eq!(min_stack.second_min(), Some(3));
+
+        min_stack.pop(); // Remove 1
+        assert_eq!(min_stack.get_min(), 3);
+        assert_eq!(min_stack.second_min(), Some(5));
+
+        // A value between the two smallest becomes the new second
+        min_stack.push(4);
+        assert_eq!(min_stack.second_min(), Some(4));
+
+        // Duplicates of the min are not a second distinct value
+        let mut same = MinStack::new();
+        same.push(2);
+        same.push(2);
+        assert_eq!(same.second_min(), None);
+    }
+
+    #[test]
+    fn test_min_positions() {
+        let mut min_stack = MinStack::new();
+        assert!(min_stack.min_positions().is_empty());
+        for val in [1, 3, 1, 2] {
+            min_stack.push(val);
+        }
+        assert_eq!(min_stack.min_positions(), vec![0, 2]);
+
+        min_stack.push(0);
+        assert_eq!(min_stack.min_positions(), vec![4]);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut bottom = MinStack::from_vec(vec![5, 2, 7]);
+        let top = MinStack::from_vec(vec![4, 1, 6]);
+        bottom.append(top);
+        assert_eq!(bottom.get_min(), 1);
+        assert_eq!(bottom.second_min(), Some(2));
+
+        let mut popped = Vec::new();
+        while !bottom.stack.is_empty() {
+            popped.push((bottom.top(), bottom.get_min()));
+            bottom.pop();
+        }
+        assert_eq!(popped, vec![(6, 1), (1, 1), (4, 2), (7, 2), (2, 2), (5, 5)]);
+
+        let mut empty = MinStack::new();
+        empty.append(MinStack::from_vec(vec![3]));
+        assert_eq!(empty.to_vec(), vec![3]);
+    }
+
+    #[test]
+    fn test_many_duplicate_minima() {
+        let mut min_stack = MinStack::new();
+        min_stack.push(5);
+        for i in 0..1000 {
+            min_stack.push(0);
+            min_stack.push(i + 1);
+        }
+        assert_eq!(min_stack.get_min(), 0);
+
+        let mut zeros_left = 1000;
+        while zeros_left > 0 {
+            if min_stack.top() == 0 {
+                zeros_left -= 1;
+            }
+            min_stack.pop();
+            let expected = if zeros_left > 0 { 0 } else { 5 };
+            assert_eq!(min_stack.get_min(), expected);
+        }
+        assert_eq!(min_stack.to_vec(), vec![5]);
+    }
+
+    #[test]
+    fn test_stack_drain_while() {
+        let mut stack: Stack<i32> = Stack::new();
+        for value in [1, 2, 5, 4, 3] {
+            stack.push(value);
+        }
+        assert_eq!(stack.drain_while(|&value| value > 2), vec![3, 4, 5]);
+        assert_eq!(stack.stack, vec![1, 2]);
+
+        assert!(stack.drain_while(|&value| value > 2).is_empty());
+        assert_eq!(stack.drain_while(|_| true), vec![2, 1]);
+        assert!(stack.is_empty());
+    }
 }
@@ -1,41 +1,159 @@
+use std::collections::{vec_deque, VecDeque};
+use std::iter::{Chain, Rev};
+
 struct PeekingIterator<I: Iterator> {
     iterator: I,
-    peeked: Option<I::Item>,
+    // Items pulled from `iterator` but not yet yielded, oldest first. Kept
+    // contiguous so the whole window can be lent out as one slice.
+    peeked: VecDeque<I::Item>,
+    // Set once `iterator` returns None; it is never polled again after
+    // that, even if it would yield more, so this iterator is fused
+    done: bool,
 }
 
 impl<I: Iterator> PeekingIterator<I> {
     fn new(iter: I) -> Self {
         PeekingIterator {
             iterator: iter,
-            peeked: None,
+            peeked: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    // Pull from the source unless it has already run dry
+    fn pull(&mut self) -> Option<I::Item> {
+        if self.done {
+            return None;
         }
+        let item = self.iterator.next();
+        self.done = item.is_none();
+        item
     }
 
     fn peek(&mut self) -> Option<&I::Item> {
-        // If we haven't peeked yet, get the next item and store it
-        if self.peeked.is_none() {
-            self.peeked = self.iterator.next();
+        self.peek_nth(0)
+    }
+
+    // Look `n` items ahead (0 is the next one), buffering everything up to it
+    fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        while self.peeked.len() <= n {
+            match self.pull() {
+                Some(item) => self.peeked.push_back(item),
+                None => break,
+            }
         }
+        // Pushing may wrap the ring buffer; pops from the front never do
+        self.peeked.make_contiguous();
 
         // Return a reference to the peeked value (if any)
-        self.peeked.as_ref()
+        self.peeked.get(n)
+    }
+
+    // Everything currently buffered, oldest first, without pulling more
+    fn peek_buffered(&self) -> &[I::Item] {
+        let (front, back) = self.peeked.as_slices();
+        debug_assert!(back.is_empty(), "peek buffer is kept contiguous");
+        front
     }
 
     fn next(&mut self) -> Option<I::Item> {
         // If we have a peeked value, return it
-        if self.peeked.is_some() {
-            // Take ownership of the peeked value, leaving None in its place
-            return self.peeked.take();
+        if let Some(item) = self.peeked.pop_front() {
+            return Some(item);
         }
 
         // Otherwise, get the next value from the iterator
-        self.iterator.next()
+        self.pull()
     }
 
     fn has_next(&mut self) -> bool {
         // Peek to load the next value if needed, then check if it exists
         self.peek().is_some()
     }
+
+    fn next_if(&mut self, pred: impl FnOnce(&I::Item) -> bool) -> Option<I::Item> {
+        // Only consume the next value when it matches, otherwise leave it buffered
+        match self.peek() {
+            Some(item) if pred(item) => self.next(),
+            _ => None,
+        }
+    }
+
+    fn next_if_eq(&mut self, expected: &I::Item) -> Option<I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.next_if(|item| item == expected)
+    }
+
+    fn take_peeked(&mut self) -> Option<I::Item> {
+        // Only drain the lookahead buffer, never advance the underlying iterator
+        self.peeked.pop_front()
+    }
+}
+
+impl<I: ExactSizeIterator> PeekingIterator<I> {
+    fn remaining_len(&self) -> usize {
+        // Buffered elements have left the source but not been yielded yet
+        self.iterator.len() + self.peeked.len()
+    }
+}
+
+// Group consecutive equal elements into (element, run length) pairs
+fn run_length_encode<I: Iterator<Item = T>, T: PartialEq + Clone>(iter: I) -> Vec<(T, usize)> {
+    let mut peeking_iterator = PeekingIterator::new(iter);
+    let mut runs = Vec::new();
+
+    while let Some(item) = peeking_iterator.next() {
+        let mut count = 1;
+        while peeking_iterator.next_if_eq(&item).is_some() {
+            count += 1;
+        }
+        runs.push((item, count));
+    }
+
+    runs
+}
+
+// Consume a run of ASCII digits and parse it, leaving the iterator on the
+// first non-digit. None if there is no digit to read or the number
+// overflows an i64; the digits are consumed either way
+fn tokenize_number<I: Iterator<Item = char>>(it: &mut PeekingIterator<I>) -> Option<i64> {
+    let mut value = Some(0i64);
+    let mut any_digits = false;
+
+    while let Some(ch) = it.next_if(char::is_ascii_digit) {
+        any_digits = true;
+        let digit = ch.to_digit(10).unwrap() as i64;
+        value = value
+            .and_then(|v| v.checked_mul(10))
+            .and_then(|v| v.checked_add(digit));
+    }
+
+    value.filter(|_| any_digits)
+}
+
+// Buffered elements have already left the source, so they are chained
+// back in front before reversing; otherwise they would be lost
+type Reversed<I> = Rev<Chain<vec_deque::IntoIter<<I as Iterator>::Item>, I>>;
+
+impl<I: DoubleEndedIterator> PeekingIterator<I> {
+    fn rev(self) -> PeekingIterator<Reversed<I>> {
+        if self.done {
+            // Only the buffer is left, and reading the source from the
+            // back could resume it, so hand the buffer over reversed
+            let mut peeked = self.peeked;
+            peeked.make_contiguous().reverse();
+            return PeekingIterator {
+                iterator: VecDeque::new().into_iter().chain(self.iterator).rev(),
+                peeked,
+                done: true,
+            };
+        }
+        // The old lookahead pointed at the front; the new one starts empty
+        // and will be filled from the back on the next peek
+        PeekingIterator::new(self.peeked.into_iter().chain(self.iterator).rev())
+    }
 }
 
 fn main() {
@@ -47,6 +165,15 @@ fn main() {
     test_single_element();
     test_alternating_peek_next();
     test_multiple_peeks();
+    test_take_peeked();
+    test_remaining_len();
+    test_next_if_eq();
+    test_run_length_encode();
+    test_rev();
+    test_peek_nth();
+    test_peek_buffered();
+    test_tokenize_number();
+    test_fused_after_exhaustion();
 
     println!("All tests passed!");
 }
@@ -54,32 +181,31 @@ fn main() {
 fn test_example_1() {
     // Recreate the example from problem statement
     let vec = vec![1, 2, 3];
-    let mut peekingIterator = PeekingIterator::new(vec.into_iter());
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
 
     // Test operations
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(1),
         "First next() should return 1"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&2),
         "peek() should return reference to 2"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(2),
         "Second next() should return 2"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(3),
         "Third next() should return 3"
     );
-    assert_eq!(
-        peekingIterator.has_next(),
-        false,
+    assert!(
+        !peeking_iterator.has_next(),
         "hasNext() should return false after all elements consumed"
     );
 
@@ -89,20 +215,19 @@ fn test_example_1() {
 fn test_empty_iterator() {
     // Test with an empty iterator
     let vec: Vec<i32> = vec![];
-    let mut peekingIterator = PeekingIterator::new(vec.into_iter());
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
 
-    assert_eq!(
-        peekingIterator.has_next(),
-        false,
+    assert!(
+        !peeking_iterator.has_next(),
         "hasNext() should return false for empty iterator"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         None,
         "peek() should return None for empty iterator"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         None,
         "next() should return None for empty iterator"
     );
@@ -113,36 +238,30 @@ fn test_empty_iterator() {
 fn test_single_element() {
     // Test with a single element
     let vec = vec![42];
-    let mut peekingIterator = PeekingIterator::new(vec.into_iter());
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
 
+    assert!(peeking_iterator.has_next(), "hasNext() should return true");
     assert_eq!(
-        peekingIterator.has_next(),
-        true,
-        "hasNext() should return true"
-    );
-    assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&42),
         "peek() should return reference to 42"
     );
-    assert_eq!(
-        peekingIterator.has_next(),
-        true,
+    assert!(
+        peeking_iterator.has_next(),
         "hasNext() should still return true after peek()"
     );
-    assert_eq!(peekingIterator.next(), Some(42), "next() should return 42");
-    assert_eq!(
-        peekingIterator.has_next(),
-        false,
+    assert_eq!(peeking_iterator.next(), Some(42), "next() should return 42");
+    assert!(
+        !peeking_iterator.has_next(),
         "hasNext() should return false after element consumed"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         None,
         "peek() should return None after all elements consumed"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         None,
         "next() should return None after all elements consumed"
     );
@@ -153,56 +272,55 @@ fn test_single_element() {
 fn test_alternating_peek_next() {
     // Test alternating between peek and next
     let vec = vec![1, 2, 3, 4, 5];
-    let mut peekingIterator = PeekingIterator::new(vec.into_iter());
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
 
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&1),
         "First peek() should return reference to 1"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(1),
         "First next() should return 1"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&2),
         "Second peek() should return reference to 2"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(2),
         "Second next() should return 2"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(3),
         "Third next() should return 3 without peeking"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&4),
         "Third peek() should return reference to 4"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&4),
         "Repeated peek() should still return reference to 4"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(4),
         "Fourth next() should return 4"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(5),
         "Fifth next() should return 5"
     );
-    assert_eq!(
-        peekingIterator.has_next(),
-        false,
+    assert!(
+        !peeking_iterator.has_next(),
         "hasNext() should return false after all elements consumed"
     );
 
@@ -212,49 +330,388 @@ fn test_alternating_peek_next() {
 fn test_multiple_peeks() {
     // Test multiple peeks before next
     let vec = vec![10, 20, 30];
-    let mut peekingIterator = PeekingIterator::new(vec.into_iter());
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
 
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&10),
         "First peek() should return reference to 10"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&10),
         "Second peek() should still return reference to 10"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&10),
         "Third peek() should still return reference to 10"
     );
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(10),
         "next() should return 10 after multiple peeks"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&20),
         "peek() after next() should return reference to 20"
     );
     assert_eq!(
-        peekingIterator.peek(),
+        peeking_iterator.peek(),
         Some(&20),
         "Multiple peeks should continue to return reference to 20"
     );
-    assert_eq!(peekingIterator.next(), Some(20), "next() should return 20");
+    assert_eq!(peeking_iterator.next(), Some(20), "next() should return 20");
     assert_eq!(
-        peekingIterator.next(),
+        peeking_iterator.next(),
         Some(30),
         "next() without peek should return 30"
     );
-    assert_eq!(
-        peekingIterator.has_next(),
-        false,
+    assert!(
+        !peeking_iterator.has_next(),
         "hasNext() should return false after all elements consumed"
     );
 
     println!("Multiple peeks test passed!");
 }
+
+fn test_take_peeked() {
+    // Test draining the lookahead buffer without advancing
+    let vec = vec![1, 2, 3];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+
+    assert_eq!(
+        peeking_iterator.take_peeked(),
+        None,
+        "take_peeked() should return None when nothing is buffered"
+    );
+    assert_eq!(
+        peeking_iterator.next(),
+        Some(1),
+        "take_peeked() without a buffer should not advance the iterator"
+    );
+    assert_eq!(
+        peeking_iterator.peek(),
+        Some(&2),
+        "peek() should return reference to 2"
+    );
+    assert_eq!(
+        peeking_iterator.take_peeked(),
+        Some(2),
+        "take_peeked() should return the buffered value"
+    );
+    assert_eq!(
+        peeking_iterator.take_peeked(),
+        None,
+        "Buffer should be empty after take_peeked()"
+    );
+    assert_eq!(
+        peeking_iterator.next(),
+        Some(3),
+        "next() should continue after the drained element"
+    );
+
+    println!("Take peeked test passed!");
+}
+
+fn test_remaining_len() {
+    // Test counting what is left without consuming
+    let vec = vec![1, 2, 3, 4];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+
+    assert_eq!(peeking_iterator.remaining_len(), 4, "Nothing consumed yet");
+    peeking_iterator.peek();
+    assert_eq!(
+        peeking_iterator.remaining_len(),
+        4,
+        "Buffered element should still count as remaining"
+    );
+    peeking_iterator.next();
+    assert_eq!(
+        peeking_iterator.remaining_len(),
+        3,
+        "next() should consume the buffered element"
+    );
+    peeking_iterator.next();
+    peeking_iterator.next();
+    peeking_iterator.next();
+    assert_eq!(
+        peeking_iterator.remaining_len(),
+        0,
+        "Exhausted iterator has nothing left"
+    );
+
+    println!("Remaining len test passed!");
+}
+
+fn test_next_if_eq() {
+    // Test conditionally consuming the next element
+    let vec = vec![1, 1, 2];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+
+    assert_eq!(
+        peeking_iterator.next_if_eq(&1),
+        Some(1),
+        "next_if_eq() should consume a matching element"
+    );
+    assert_eq!(
+        peeking_iterator.next_if_eq(&2),
+        None,
+        "next_if_eq() should not consume a different element"
+    );
+    assert_eq!(
+        peeking_iterator.next(),
+        Some(1),
+        "Rejected element should still be next"
+    );
+    assert_eq!(peeking_iterator.next_if_eq(&2), Some(2));
+    assert_eq!(
+        peeking_iterator.next_if_eq(&2),
+        None,
+        "next_if_eq() should return None when exhausted"
+    );
+
+    println!("Next if eq test passed!");
+}
+
+fn test_run_length_encode() {
+    // Test grouping consecutive runs
+    let chars = vec!['a', 'a', 'b', 'c', 'c', 'c'];
+    assert_eq!(
+        run_length_encode(chars.into_iter()),
+        vec![('a', 2), ('b', 1), ('c', 3)],
+        "Consecutive equal elements should be grouped"
+    );
+
+    let empty: Vec<char> = vec![];
+    assert_eq!(
+        run_length_encode(empty.into_iter()),
+        vec![],
+        "Empty input should produce no runs"
+    );
+
+    println!("Run length encode test passed!");
+}
+
+fn test_rev() {
+    // Test reversing before anything was peeked
+    let vec = vec![1, 2, 3];
+    let mut reversed = PeekingIterator::new(vec.into_iter()).rev();
+
+    assert_eq!(
+        reversed.peek(),
+        Some(&3),
+        "peek() should return reference to 3"
+    );
+    assert_eq!(reversed.next(), Some(3), "First next() should return 3");
+    assert_eq!(reversed.next(), Some(2), "Second next() should return 2");
+    assert_eq!(
+        reversed.peek(),
+        Some(&1),
+        "peek() should return reference to 1"
+    );
+    assert_eq!(reversed.next(), Some(1), "Third next() should return 1");
+    assert!(
+        !reversed.has_next(),
+        "hasNext() should return false after all elements consumed"
+    );
+
+    // Test that a buffered element is kept when reversing
+    let vec = vec![1, 2, 3, 4];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+    peeking_iterator.next();
+    assert_eq!(peeking_iterator.peek(), Some(&2));
+    let mut reversed = peeking_iterator.rev();
+    assert_eq!(
+        reversed.peek(),
+        Some(&4),
+        "Reversed peek() should see the back"
+    );
+    assert_eq!(reversed.next(), Some(4));
+    assert_eq!(reversed.next(), Some(3));
+    assert_eq!(
+        reversed.next(),
+        Some(2),
+        "Previously peeked element should come last"
+    );
+    assert_eq!(reversed.next(), None);
+
+    println!("Rev test passed!");
+}
+
+fn test_peek_nth() {
+    // Test looking several items ahead
+    let vec = vec![1, 2, 3];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+
+    assert_eq!(
+        peeking_iterator.peek_nth(1),
+        Some(&2),
+        "peek_nth(1) should see 2"
+    );
+    assert_eq!(
+        peeking_iterator.peek(),
+        Some(&1),
+        "peek() should still see 1"
+    );
+    assert_eq!(
+        peeking_iterator.peek_nth(3),
+        None,
+        "Nothing four items ahead"
+    );
+    assert_eq!(
+        peeking_iterator.remaining_len(),
+        3,
+        "Buffered items still remain"
+    );
+    assert_eq!(peeking_iterator.next(), Some(1));
+    assert_eq!(
+        peeking_iterator.peek_nth(1),
+        Some(&3),
+        "Window moves with next()"
+    );
+    assert_eq!(peeking_iterator.next(), Some(2));
+    assert_eq!(peeking_iterator.next(), Some(3));
+    assert_eq!(peeking_iterator.next(), None);
+
+    println!("Peek nth test passed!");
+}
+
+fn test_peek_buffered() {
+    // Test exposing the lookahead window as a slice
+    let vec = vec![1, 2, 3, 4, 5];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+    assert!(
+        peeking_iterator.peek_buffered().is_empty(),
+        "Nothing buffered before peeking"
+    );
+
+    peeking_iterator.peek_nth(2);
+    assert_eq!(
+        peeking_iterator.peek_buffered(),
+        &[1, 2, 3],
+        "peek_nth(2) should buffer three items"
+    );
+    assert_eq!(
+        peeking_iterator.remaining_len(),
+        5,
+        "peek_buffered() should not pull from the source"
+    );
+
+    // Shift the window so the ring buffer has to wrap
+    peeking_iterator.next();
+    peeking_iterator.next();
+    peeking_iterator.peek_nth(2);
+    assert_eq!(
+        peeking_iterator.peek_buffered(),
+        &[3, 4, 5],
+        "Window should stay in order after moving"
+    );
+
+    println!("Peek buffered test passed!");
+}
+
+fn test_tokenize_number() {
+    // Test reading numbers out of an expression with one char of lookahead
+    let mut peeking_iterator = PeekingIterator::new("123+45".chars());
+    assert_eq!(
+        tokenize_number(&mut peeking_iterator),
+        Some(123),
+        "First number should be 123"
+    );
+    assert_eq!(
+        peeking_iterator.peek(),
+        Some(&'+'),
+        "Iterator should stop on the operator"
+    );
+    assert_eq!(
+        tokenize_number(&mut peeking_iterator),
+        None,
+        "No number starts at the operator"
+    );
+    assert_eq!(
+        peeking_iterator.next(),
+        Some('+'),
+        "Operator is left in place"
+    );
+    assert_eq!(
+        tokenize_number(&mut peeking_iterator),
+        Some(45),
+        "Second number should be 45"
+    );
+    assert_eq!(peeking_iterator.peek(), None, "Input should be exhausted");
+
+    let mut peeking_iterator = PeekingIterator::new("99999999999999999999x".chars());
+    assert_eq!(
+        tokenize_number(&mut peeking_iterator),
+        None,
+        "Overflowing number should give None"
+    );
+    assert_eq!(
+        peeking_iterator.peek(),
+        Some(&'x'),
+        "Overflowing digits should still be consumed"
+    );
+
+    println!("Tokenize number test passed!");
+}
+
+// Yields 1, then None, then 2, then None forever; not fused on its own
+struct Flaky {
+    calls: usize,
+}
+
+impl Iterator for Flaky {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        self.calls += 1;
+        match self.calls {
+            1 => Some(1),
+            3 => Some(2),
+            _ => None,
+        }
+    }
+}
+
+fn test_fused_after_exhaustion() {
+    // Test that a source yielding again after None is not resumed
+    let mut peeking_iterator = PeekingIterator::new(Flaky { calls: 0 });
+    assert_eq!(peeking_iterator.next(), Some(1), "First item should be 1");
+    assert_eq!(peeking_iterator.peek(), None, "Source has run dry");
+    assert_eq!(peeking_iterator.next(), None, "Still dry after peek");
+    assert!(!peeking_iterator.has_next(), "has_next() should stay false");
+    assert_eq!(peeking_iterator.peek_nth(3), None, "Nothing further ahead");
+    assert_eq!(
+        peeking_iterator.iterator.calls, 2,
+        "Source should not be polled after its first None"
+    );
+
+    // Buffered items still come out after the source is done
+    let vec = vec![1, 2];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+    assert_eq!(peeking_iterator.peek_nth(5), None, "Only two items");
+    assert_eq!(
+        peeking_iterator.next(),
+        Some(1),
+        "Buffer survives exhaustion"
+    );
+    assert_eq!(
+        peeking_iterator.next(),
+        Some(2),
+        "Buffer survives exhaustion"
+    );
+    assert_eq!(peeking_iterator.next(), None, "Then it is empty");
+
+    let vec = vec![1, 2, 3];
+    let mut peeking_iterator = PeekingIterator::new(vec.into_iter());
+    peeking_iterator.peek_nth(5);
+    let mut reversed = peeking_iterator.rev();
+    assert_eq!(reversed.next(), Some(3), "Reversing keeps the buffer");
+    assert_eq!(reversed.next(), Some(2), "Reversing keeps the buffer");
+    assert_eq!(reversed.next(), Some(1), "Reversing keeps the buffer");
+    assert_eq!(reversed.next(), None, "Reversed iterator is fused too");
+
+    println!("Fused after exhaustion test passed!");
+}
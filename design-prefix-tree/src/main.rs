@@ -3,72 +3,898 @@
 // where the keys are usually strings.
 // It is commonly used for autocomplete and spell checking.
 
+// How a node stores its children. The Trie is generic over this so the
+// plain array layout and the compact bitmask layout share every method.
+trait Children: Default {
+    fn get(&self, index: usize) -> Option<&TrieNode<Self>>;
+    #[allow(dead_code)]
+    fn get_mut(&mut self, index: usize) -> Option<&mut TrieNode<Self>>;
+    // `make` supplies the node when the slot is empty
+    fn get_or_insert_with(
+        &mut self,
+        index: usize,
+        make: impl FnOnce() -> Box<TrieNode<Self>>,
+    ) -> &mut TrieNode<Self>;
+    #[allow(dead_code)]
+    fn take(&mut self, index: usize) -> Option<Box<TrieNode<Self>>>;
+    // Present children as (index, node), in a..z order
+    #[allow(dead_code)]
+    fn iter(&self) -> impl Iterator<Item = (usize, &TrieNode<Self>)>;
+}
+
+// One slot per letter, even when most of them are empty
+#[derive(Debug, Default)]
+struct ArrayChildren([Option<Box<TrieNode>>; 26]);
+
+impl Children for ArrayChildren {
+    fn get(&self, index: usize) -> Option<&TrieNode> {
+        self.0[index].as_deref()
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut TrieNode> {
+        self.0[index].as_deref_mut()
+    }
+
+    fn get_or_insert_with(
+        &mut self,
+        index: usize,
+        make: impl FnOnce() -> Box<TrieNode>,
+    ) -> &mut TrieNode {
+        self.0[index].get_or_insert_with(make)
+    }
+
+    fn take(&mut self, index: usize) -> Option<Box<TrieNode>> {
+        self.0[index].take()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (usize, &TrieNode)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, child)| child.as_deref().map(|node| (i, node)))
+    }
+}
+
+// Bit `i` of `mask` is set when letter `i` has a child. `nodes` only holds
+// the present children, in letter order, so a child's slot is the number
+// of set bits below its own bit.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct BitmaskChildren {
+    mask: u32,
+    // Boxed so inserting a child shifts pointers rather than whole nodes
+    #[allow(clippy::vec_box)]
+    nodes: Vec<Box<CompactTrieNode>>,
+}
+
+#[allow(dead_code)]
+impl BitmaskChildren {
+    fn slot(&self, index: usize) -> usize {
+        (self.mask & ((1u32 << index) - 1)).count_ones() as usize
+    }
+
+    fn has(&self, index: usize) -> bool {
+        self.mask & (1u32 << index) != 0
+    }
+}
+
+impl Children for BitmaskChildren {
+    fn get(&self, index: usize) -> Option<&CompactTrieNode> {
+        if !self.has(index) {
+            return None;
+        }
+        Some(&self.nodes[self.slot(index)])
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut CompactTrieNode> {
+        if !self.has(index) {
+            return None;
+        }
+        let slot = self.slot(index);
+        Some(&mut self.nodes[slot])
+    }
+
+    fn get_or_insert_with(
+        &mut self,
+        index: usize,
+        make: impl FnOnce() -> Box<CompactTrieNode>,
+    ) -> &mut CompactTrieNode {
+        let slot = self.slot(index);
+        if !self.has(index) {
+            self.mask |= 1u32 << index;
+            self.nodes.insert(slot, make());
+        }
+        &mut self.nodes[slot]
+    }
+
+    fn take(&mut self, index: usize) -> Option<Box<CompactTrieNode>> {
+        if !self.has(index) {
+            return None;
+        }
+        let slot = self.slot(index);
+        self.mask &= !(1u32 << index);
+        Some(self.nodes.remove(slot))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (usize, &CompactTrieNode)> {
+        (0..26)
+            .filter(|&i| self.has(i))
+            .zip(self.nodes.iter().map(|node| node.as_ref()))
+    }
+}
+
 #[derive(Debug)]
-struct TrieNode {
-    children: [Option<Box<TrieNode>>; 26],
+struct TrieNode<C = ArrayChildren> {
+    children: C,
     is_end: bool,
+    #[allow(dead_code)]
+    weight: u64,
+    // Insertion sequence number of the word ending here; larger is newer
+    seq: u64,
 }
 
-impl TrieNode {
+#[allow(dead_code)]
+type CompactTrieNode = TrieNode<BitmaskChildren>;
+
+impl<C: Children> TrieNode<C> {
     fn new() -> Self {
         Self {
-            // Create an array of None values for children
-            children: Default::default(),
+            // Start with no children at all
+            children: C::default(),
             is_end: false,
+            weight: 0,
+            seq: 0,
+        }
+    }
+
+    // Take this subtree apart and push every node, reset to empty, onto
+    // `pool`; nodes beyond POOL_LIMIT are simply freed
+    #[allow(dead_code)]
+    fn recycle(mut self: Box<Self>, pool: &mut Vec<Box<Self>>) {
+        for index in 0..26 {
+            if let Some(child) = self.children.take(index) {
+                child.recycle(pool);
+            }
+        }
+        if pool.len() < POOL_LIMIT {
+            self.is_end = false;
+            self.weight = 0;
+            self.seq = 0;
+            pool.push(self);
+        }
+    }
+
+    // Fold `other` into this node: terminal flags are unioned, weights
+    // added, and other's sequence numbers moved up by `seq_offset` so its
+    // words rank as newer; subtrees this node lacks are moved over whole
+    #[allow(dead_code)]
+    fn absorb(&mut self, mut other: Self, seq_offset: u64) {
+        if other.is_end {
+            self.is_end = true;
+            self.weight += other.weight;
+            self.seq = self.seq.max(other.seq + seq_offset);
+        }
+        for index in 0..26 {
+            let Some(mut child) = other.children.take(index) else {
+                continue;
+            };
+            match self.children.get_mut(index) {
+                Some(mine) => mine.absorb(*child, seq_offset),
+                None => {
+                    child.shift_seq(seq_offset);
+                    self.children.get_or_insert_with(index, || child);
+                }
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    fn shift_seq(&mut self, offset: u64) {
+        if self.is_end {
+            self.seq += offset;
+        }
+        for index in 0..26 {
+            if let Some(child) = self.children.get_mut(index) {
+                child.shift_seq(offset);
+            }
+        }
+    }
+
+    // Detach the subtree at `path` below this node, then on the way back
+    // up drop every child on the path that no longer leads to a word
+    #[allow(dead_code)]
+    fn remove_path(&mut self, path: &[usize], pool: &mut Vec<Box<Self>>) -> usize {
+        let (&index, rest) = path.split_first().unwrap();
+        if rest.is_empty() {
+            let Some(subtree) = self.children.take(index) else {
+                return 0;
+            };
+            let removed = subtree.count_words();
+            subtree.recycle(pool);
+            return removed;
+        }
+
+        let Some(child) = self.children.get_mut(index) else {
+            return 0;
+        };
+        let removed = child.remove_path(rest, pool);
+        if !child.is_end && !child.has_children() {
+            self.children.take(index).unwrap().recycle(pool);
+        }
+        removed
+    }
+
+    #[allow(dead_code)]
+    fn has_children(&self) -> bool {
+        self.children.iter().next().is_some()
+    }
+
+    // Count the words stored in this node's subtree, including itself
+    #[allow(dead_code)]
+    fn count_words(&self) -> usize {
+        let below: usize = self
+            .children
+            .iter()
+            .map(|(_, child)| child.count_words())
+            .sum();
+        below + self.is_end as usize
+    }
+
+    // Count this node and every node below it
+    #[allow(dead_code)]
+    fn count_nodes(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(|(_, child)| child.count_nodes())
+            .sum::<usize>()
+    }
+
+    // Depth-first walk collecting (word, weight) for every terminal in
+    // this subtree; children are visited a..z so words come out sorted
+    #[allow(dead_code)]
+    fn collect_words(&self, path: &mut String, out: &mut Vec<(String, u64)>) {
+        self.for_each_word(path, &mut |word, node| {
+            out.push((word.to_string(), node.weight))
+        });
+    }
+
+    // Call `f` with the word and terminal node for every word in this
+    // subtree, in the same a..z order as collect_words
+    #[allow(dead_code)]
+    fn for_each_word(&self, path: &mut String, f: &mut impl FnMut(&str, &Self)) {
+        if self.is_end {
+            f(path, self);
+        }
+        for (i, node) in self.children.iter() {
+            path.push((b'a' + i as u8) as char);
+            node.for_each_word(path, f);
+            path.pop();
+        }
+    }
+
+    // Keeps the `k` words below this node closest to `target`, by distance
+    // then alphabetically. `row` holds the distances for `path` itself.
+    // Words arrive in alphabetical order, so a subtree whose best possible
+    // distance cannot beat the current worst is skipped.
+    #[allow(dead_code)]
+    fn collect_nearest(
+        &self,
+        path: &mut String,
+        row: &[usize],
+        target: &[char],
+        k: usize,
+        best: &mut Vec<(usize, String)>,
+    ) {
+        if self.is_end {
+            let distance = row[target.len()];
+            if best.len() < k || distance < best[k - 1].0 {
+                let at = best.partition_point(|(d, _)| *d <= distance);
+                best.insert(at, (distance, path.clone()));
+                best.truncate(k);
+            }
+        }
+        if best.len() == k && row.iter().min().unwrap() >= &best[k - 1].0 {
+            return;
+        }
+        for (i, node) in self.children.iter() {
+            let ch = (b'a' + i as u8) as char;
+            path.push(ch);
+            node.collect_nearest(path, &next_row(row, ch, target), target, k, best);
+            path.pop();
+        }
+    }
+
+    // Like collect_words, but stops descending as soon as `out` holds
+    // `max` words; since children are visited a..z those are the
+    // alphabetically smallest ones
+    #[allow(dead_code)]
+    fn collect_limited(&self, path: &mut String, max: usize, out: &mut Vec<String>) {
+        if out.len() >= max {
+            return;
+        }
+        if self.is_end {
+            out.push(path.clone());
+        }
+        for (i, node) in self.children.iter() {
+            if out.len() >= max {
+                return;
+            }
+            path.push((b'a' + i as u8) as char);
+            node.collect_limited(path, max, out);
+            path.pop();
+        }
+    }
+
+    // Backtracking match of a glob pattern against this subtree:
+    // '?' consumes exactly one letter, '*' consumes zero or more
+    #[allow(dead_code)]
+    fn matches_glob(&self, pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => self.is_end,
+            Some(('*', rest)) => {
+                // Either the star matches nothing here, or it swallows one
+                // more letter and stays active
+                self.matches_glob(rest)
+                    || self
+                        .children
+                        .iter()
+                        .any(|(_, child)| child.matches_glob(pattern))
+            }
+            Some(('?', rest)) => self
+                .children
+                .iter()
+                .any(|(_, child)| child.matches_glob(rest)),
+            Some((&ch, rest)) => match char_index(ch) {
+                Ok(index) => self
+                    .children
+                    .get(index)
+                    .is_some_and(|child| child.matches_glob(rest)),
+                Err(_) => false,
+            },
+        }
+    }
+
+    // Like collect_words, but only terminals exactly `remaining` levels
+    // down; never descends past that depth
+    #[allow(dead_code)]
+    fn collect_at_depth(&self, remaining: usize, path: &mut String, out: &mut Vec<String>) {
+        if remaining == 0 {
+            if self.is_end {
+                out.push(path.clone());
+            }
+            return;
+        }
+        for (i, node) in self.children.iter() {
+            path.push((b'a' + i as u8) as char);
+            node.collect_at_depth(remaining - 1, path, out);
+            path.pop();
+        }
+    }
+
+    // Preorder: this node's header, then each child's subtree in a..z order
+    #[allow(dead_code)]
+    fn write_compact(&self, out: &mut Vec<u8>) {
+        let mask = self
+            .children
+            .iter()
+            .fold(0u32, |mask, (i, _)| mask | (1 << i));
+        let header = mask | ((self.is_end as u32) << END_BIT);
+        out.extend_from_slice(&header.to_le_bytes());
+        for (_, child) in self.children.iter() {
+            child.write_compact(out);
         }
     }
 }
 
-struct Trie {
-    root: TrieNode,
+// Compact format: MAGIC, a version byte, then one little-endian u32 per
+// node in preorder. Bits 0..26 say which letters have a child, bit 26 is
+// the is_end flag and the rest must be zero. Weights and insertion order
+// are not stored.
+#[allow(dead_code)]
+const MAGIC: &[u8; 4] = b"TRIE";
+#[allow(dead_code)]
+const COMPACT_VERSION: u8 = 1;
+#[allow(dead_code)]
+const END_BIT: u32 = 26;
+
+#[allow(dead_code)]
+fn read_header(bytes: &[u8], pos: &mut usize) -> Result<(u32, bool), TrieError> {
+    let Some(chunk) = bytes.get(*pos..*pos + 4) else {
+        return Err(TrieError::Corrupt("truncated node"));
+    };
+    *pos += 4;
+    let header = u32::from_le_bytes(chunk.try_into().unwrap());
+    if header >> (END_BIT + 1) != 0 {
+        return Err(TrieError::Corrupt("reserved bits set"));
+    }
+    Ok((header & ((1 << END_BIT) - 1), header & (1 << END_BIT) != 0))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TrieError {
+    // Only lowercase ASCII letters can be stored
+    InvalidChar(char),
+    // deserialize_compact input does not start with MAGIC
+    #[allow(dead_code)]
+    BadHeader,
+    #[allow(dead_code)]
+    UnsupportedVersion(u8),
+    // The header was fine but the node data is not
+    #[allow(dead_code)]
+    Corrupt(&'static str),
+}
+
+impl std::fmt::Display for TrieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TrieError::InvalidChar(ch) => write!(f, "invalid character {:?}, expected a-z", ch),
+            TrieError::BadHeader => write!(f, "not a compact trie: bad magic header"),
+            TrieError::UnsupportedVersion(v) => write!(f, "unsupported compact trie version {}", v),
+            TrieError::Corrupt(reason) => write!(f, "corrupt compact trie: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TrieError {}
+
+// Map a lowercase ASCII letter to its child slot
+fn char_index(ch: char) -> Result<usize, TrieError> {
+    if ch.is_ascii_lowercase() {
+        Ok((ch as u8 - b'a') as usize)
+    } else {
+        Err(TrieError::InvalidChar(ch))
+    }
+}
+
+// Levenshtein distance over chars: insertions, deletions and
+// substitutions all cost one
+#[allow(dead_code)]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for ca in a.chars() {
+        row = next_row(&row, ca, &b);
+    }
+    row[b.len()]
+}
+
+// One Levenshtein row step: given prev[j] = distance(prefix, target[..j]),
+// returns the row for prefix + ch
+#[allow(dead_code)]
+fn next_row(prev: &[usize], ch: char, target: &[char]) -> Vec<usize> {
+    let mut row = vec![prev[0] + 1; target.len() + 1];
+    for (j, &tc) in target.iter().enumerate() {
+        let substitute = prev[j] + (ch != tc) as usize;
+        row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+    }
+    row
+}
+
+// Most nodes kept around for reuse after removals; the pool never holds
+// more than this, so churn cannot pin an unbounded amount of memory
+#[allow(dead_code)]
+const POOL_LIMIT: usize = 1024;
+
+struct Trie<C = ArrayChildren> {
+    root: TrieNode<C>,
+    // Handed out to each inserted word so recent_completions can rank them
+    next_seq: u64,
+    // Empty nodes reclaimed by remove_prefix, handed out again on insert
+    // so insert/remove churn does not go back to the allocator every time
+    pool: Vec<Box<TrieNode<C>>>,
 }
 
-/**
- * `&self` means the method takes an immutable reference.
- * If you need a mutable reference, change it to `&mut self` instead.
- */
 impl Trie {
     fn new() -> Self {
         Self {
             root: TrieNode::new(),
+            next_seq: 0,
+            pool: Vec::new(),
         }
     }
 
-    fn insert(&mut self, word: String) {
-        let mut current = &mut self.root;
-        for ch in word.chars() {
-            let index = (ch as u8 - b'a') as usize;
-            if current.children[index].is_none() {
-                current.children[index] = Some(Box::new(TrieNode::new()));
+    #[allow(dead_code)]
+    fn deserialize_compact(bytes: &[u8]) -> Result<Trie, TrieError> {
+        let Some(body) = bytes.strip_prefix(MAGIC) else {
+            return Err(TrieError::BadHeader);
+        };
+        match body.first() {
+            Some(&COMPACT_VERSION) => {}
+            Some(&version) => return Err(TrieError::UnsupportedVersion(version)),
+            None => return Err(TrieError::Corrupt("missing version")),
+        }
+
+        // Rebuild with an explicit stack so hostile input cannot overflow
+        // the call stack. Each frame is (node, children still to read,
+        // letter under which the node hangs off its parent).
+        let mut pos = 1;
+        let (mask, is_end) = read_header(body, &mut pos)?;
+        let mut root = Box::new(TrieNode::<ArrayChildren>::new());
+        root.is_end = is_end;
+        let mut stack = vec![(root, mask, 0)];
+        loop {
+            let (_, pending, _) = stack.last_mut().unwrap();
+            if *pending == 0 {
+                let (node, _, letter) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((parent, _, _)) => {
+                        parent.children.get_or_insert_with(letter, || node);
+                    }
+                    None => {
+                        if pos != body.len() {
+                            return Err(TrieError::Corrupt("trailing bytes"));
+                        }
+                        return Ok(Trie {
+                            root: *node,
+                            ..Trie::new()
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let letter = pending.trailing_zeros() as usize;
+            *pending &= *pending - 1;
+            let (mask, is_end) = read_header(body, &mut pos)?;
+            // serialize_compact never writes a leaf that ends no word
+            if mask == 0 && !is_end {
+                return Err(TrieError::Corrupt("dead branch"));
             }
-            // Move to the next node
-            current = current.children[index].as_mut().unwrap();
+            let mut child = Box::new(TrieNode::<ArrayChildren>::new());
+            child.is_end = is_end;
+            stack.push((child, mask, letter));
         }
-        current.is_end = true;
+    }
+}
+
+impl Trie<BitmaskChildren> {
+    // Same API as `Trie::new`, but nodes only allocate the children they use
+    #[allow(dead_code)]
+    fn new_compact() -> Self {
+        Self {
+            root: TrieNode::new(),
+            next_seq: 0,
+            pool: Vec::new(),
+        }
+    }
+}
+
+/**
+ * `&self` means the method takes an immutable reference.
+ * If you need a mutable reference, change it to `&mut self` instead.
+ */
+impl<C: Children> Trie<C> {
+    // Words containing anything outside a..z are skipped; use try_insert
+    // to find out which character was rejected
+    fn insert(&mut self, word: String) {
+        let _ = self.try_insert(&word);
+    }
+
+    fn try_insert(&mut self, word: &str) -> Result<(), TrieError> {
+        self.insert_path(word)?.is_end = true;
+        Ok(())
+    }
+
+    // Create the path for `word` and mark it as the newest insertion
+    fn insert_path(&mut self, word: &str) -> Result<&mut TrieNode<C>, TrieError> {
+        self.next_seq += 1;
+        let seq = self.next_seq;
+        let node = self.walk_or_create(word)?;
+        node.seq = seq;
+        Ok(node)
+    }
+
+    // Add every word of `other` as if it had been inserted here after all
+    // of this trie's words; a word in both keeps the sum of its weights
+    #[allow(dead_code)]
+    fn merge(&mut self, other: Trie<C>) {
+        self.root.absorb(other.root, self.next_seq);
+        self.next_seq += other.next_seq;
+        let room = POOL_LIMIT.saturating_sub(self.pool.len());
+        self.pool.extend(other.pool.into_iter().take(room));
     }
 
     fn search(&self, word: String) -> bool {
-        let mut current = &self.root;
-        for ch in word.chars() {
-            let index = (ch as u8 - b'a') as usize;
-            match &current.children[index] {
-                None => return false,
-                Some(node) => current = node,
-            }
+        match self.find_node(&word) {
+            Some(node) => node.is_end,
+            None => false,
         }
-        current.is_end
     }
 
     fn starts_with(&self, prefix: String) -> bool {
+        self.find_node(&prefix).is_some()
+    }
+
+    // Number of stored words, counted from the terminal flags on every
+    // call rather than kept in a field, so it always matches the structure
+    #[allow(dead_code)]
+    fn word_count(&self) -> usize {
+        self.root.count_words()
+    }
+
+    // `prefix` leads on to a longer word but is not a word itself. Every
+    // leaf ends a word, so any child below a non-word node proves it.
+    #[allow(dead_code)]
+    fn is_strict_prefix(&self, prefix: String) -> bool {
+        match self.find_node(&prefix) {
+            Some(node) => !node.is_end && node.has_children(),
+            None => false,
+        }
+    }
+
+    // Walk down to the node for `prefix`; out-of-range characters can never
+    // be stored, so they simply mean "not found"
+    fn find_node(&self, prefix: &str) -> Option<&TrieNode<C>> {
         let mut current = &self.root;
         for ch in prefix.chars() {
-            let index = (ch as u8 - b'a') as usize;
-            match &current.children[index] {
-                None => return false,
-                Some(node) => current = node,
+            current = current.children.get(char_index(ch).ok()?)?;
+        }
+        Some(current)
+    }
+
+    // Validate the whole word before creating any nodes, so a rejected word
+    // does not leave a dangling path behind
+    fn walk_or_create(&mut self, word: &str) -> Result<&mut TrieNode<C>, TrieError> {
+        let indices = word
+            .chars()
+            .map(char_index)
+            .collect::<Result<Vec<_>, _>>()?;
+        let pool = &mut self.pool;
+        let mut current = &mut self.root;
+        for index in indices {
+            // Move to the next node, creating it if needed
+            current = current.children.get_or_insert_with(index, || {
+                pool.pop().unwrap_or_else(|| Box::new(TrieNode::new()))
+            });
+        }
+        Ok(current)
+    }
+
+    #[allow(dead_code)]
+    fn insert_weighted(&mut self, word: String, weight: u64) {
+        if let Ok(node) = self.insert_path(&word) {
+            node.is_end = true;
+            node.weight += weight;
+        }
+    }
+
+    #[allow(dead_code)]
+    fn top_completions(&self, prefix: String, k: usize) -> Vec<(String, u64)> {
+        let Some(current) = self.find_node(&prefix) else {
+            return Vec::new();
+        };
+
+        let mut path = prefix;
+        let mut words = Vec::new();
+        current.collect_words(&mut path, &mut words);
+
+        // Heaviest first, ties broken lexicographically
+        words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        words.truncate(k);
+        words
+    }
+
+    // Every word under `prefix` in lexicographic order. A word has one
+    // terminal node however often it was inserted, so each shows up once.
+    #[allow(dead_code)]
+    fn words_with_prefix(&self, prefix: String) -> Vec<String> {
+        let Some(current) = self.find_node(&prefix) else {
+            return Vec::new();
+        };
+
+        let mut words = Vec::new();
+        current.for_each_word(&mut prefix.clone(), &mut |word, _| {
+            words.push(word.to_string())
+        });
+        words
+    }
+
+    // Up to `max` words under `prefix` in lexicographic order, without
+    // walking the rest of the subtree once the cap is hit
+    #[allow(dead_code)]
+    fn words_with_prefix_limited(&self, prefix: String, max: usize) -> Vec<String> {
+        let Some(current) = self.find_node(&prefix) else {
+            return Vec::new();
+        };
+
+        let mut words = Vec::new();
+        current.collect_limited(&mut prefix.clone(), max, &mut words);
+        words
+    }
+
+    // The k most recently inserted words under `prefix`, newest first;
+    // re-inserting a word makes it the newest again
+    #[allow(dead_code)]
+    fn recent_completions(&self, prefix: String, k: usize) -> Vec<String> {
+        let Some(current) = self.find_node(&prefix) else {
+            return Vec::new();
+        };
+
+        let mut words = Vec::new();
+        current.for_each_word(&mut prefix.clone(), &mut |word, node| {
+            words.push((node.seq, word.to_string()))
+        });
+
+        words.sort_by_key(|(seq, _)| std::cmp::Reverse(*seq));
+        words.into_iter().take(k).map(|(_, word)| word).collect()
+    }
+
+    // Every word, oldest insertion first. Uses the same sequence numbers as
+    // recent_completions, so a re-inserted word moves to its latest position
+    #[allow(dead_code)]
+    fn words_in_insertion_order(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        self.root
+            .for_each_word(&mut String::new(), &mut |word, node| {
+                words.push((node.seq, word.to_string()))
+            });
+
+        words.sort_unstable_by_key(|&(seq, _)| seq);
+        words.into_iter().map(|(_, word)| word).collect()
+    }
+
+    // Spellcheck candidates: words sharing the longest prefix with `word`
+    // come first, then the smallest edit distance, then alphabetical order.
+    // A stored exact match therefore always ranks first.
+    #[allow(dead_code)]
+    fn suggest(&self, word: String, k: usize) -> Vec<String> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let target: Vec<char> = word.chars().collect();
+
+        // Walk the path `word` shares with the trie, keeping one distance
+        // row per depth so the branches below can extend it
+        let mut path = vec![&self.root];
+        let mut rows = vec![(0..=target.len()).collect::<Vec<_>>()];
+        for &ch in &target {
+            let Some(child) = char_index(ch)
+                .ok()
+                .and_then(|i| path.last().unwrap().children.get(i))
+            else {
+                break;
+            };
+            rows.push(next_row(rows.last().unwrap(), ch, &target));
+            path.push(child);
+        }
+
+        // Words branching off at depth d share exactly d chars, so deeper
+        // branches always rank first and the walk back up stops at k
+        let mut suggestions = Vec::new();
+        for depth in (0..path.len()).rev() {
+            let node = path[depth];
+            let row = &rows[depth];
+            let mut prefix: String = target[..depth].iter().collect();
+            let skip = target.get(depth).and_then(|&ch| char_index(ch).ok());
+            let wanted = k - suggestions.len();
+            let mut best = Vec::new();
+            if node.is_end {
+                best.push((row[target.len()], prefix.clone()));
+            }
+            for (i, child) in node.children.iter() {
+                if Some(i) != skip {
+                    let ch = (b'a' + i as u8) as char;
+                    prefix.push(ch);
+                    child.collect_nearest(
+                        &mut prefix,
+                        &next_row(row, ch, &target),
+                        &target,
+                        wanted,
+                        &mut best,
+                    );
+                    prefix.pop();
+                }
+            }
+            suggestions.extend(best.into_iter().map(|(_, candidate)| candidate));
+            if suggestions.len() == k {
+                break;
+            }
+        }
+        suggestions
+    }
+
+    // Removes every word starting with `prefix` and returns how many went.
+    // Ancestors left with no word and no children are pruned as well, so
+    // every leaf in the trie ends a word.
+    #[allow(dead_code)]
+    fn remove_prefix(&mut self, prefix: String) -> usize {
+        let Ok(indices) = prefix
+            .chars()
+            .map(char_index)
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return 0;
+        };
+        if indices.is_empty() {
+            // Every word starts with the empty prefix
+            let removed = self.root.count_words();
+            for index in 0..26 {
+                if let Some(child) = self.root.children.take(index) {
+                    child.recycle(&mut self.pool);
+                }
             }
+            self.root.is_end = false;
+            self.root.weight = 0;
+            self.root.seq = 0;
+            return removed;
         }
-        true
+        self.root.remove_path(&indices, &mut self.pool)
+    }
+
+    #[allow(dead_code)]
+    fn search_glob(&self, pattern: String) -> bool {
+        let mut chars: Vec<char> = pattern.chars().collect();
+        // "**" matches the same as "*", and collapsing runs keeps the
+        // backtracking from blowing up
+        chars.dedup_by(|a, b| *a == '*' && *b == '*');
+        self.root.matches_glob(&chars)
+    }
+
+    // All stored words with exactly `len` letters, sorted
+    #[allow(dead_code)]
+    fn words_of_length(&self, len: usize) -> Vec<String> {
+        let mut words = Vec::new();
+        self.root
+            .collect_at_depth(len, &mut String::new(), &mut words);
+        words
+    }
+
+    // Stored words that `query` starts with, shortest first
+    #[allow(dead_code)]
+    fn prefixes_of(&self, query: String) -> Vec<String> {
+        let mut prefixes = Vec::new();
+        let mut current = &self.root;
+        for (end, ch) in query.char_indices() {
+            if current.is_end {
+                prefixes.push(query[..end].to_string());
+            }
+            let Some(child) = char_index(ch).ok().and_then(|i| current.children.get(i)) else {
+                return prefixes;
+            };
+            current = child;
+        }
+        if current.is_end {
+            prefixes.push(query);
+        }
+        prefixes
+    }
+
+    // Longest string every stored word starts with: follow the single
+    // child until the path forks or a word ends.
+    #[allow(dead_code)]
+    fn longest_common_prefix(&self) -> String {
+        let mut prefix = String::new();
+        let mut current = &self.root;
+        while !current.is_end {
+            let mut children = current.children.iter();
+            let (Some((i, child)), None) = (children.next(), children.next()) else {
+                break;
+            };
+            prefix.push((b'a' + i as u8) as char);
+            current = child;
+        }
+        prefix
+    }
+
+    #[allow(dead_code)]
+    fn serialize_compact(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(COMPACT_VERSION);
+        self.root.write_compact(&mut out);
+        out
+    }
+
+    // Nodes waiting in the reuse pool
+    #[allow(dead_code)]
+    fn pooled_node_count(&self) -> usize {
+        self.pool.len()
+    }
+
+    // Number of nodes in the trie, including the root
+    #[allow(dead_code)]
+    fn node_count(&self) -> usize {
+        self.root.count_nodes()
     }
 }
 
@@ -135,4 +961,566 @@ mod tests {
         assert!(!trie.search("anything".to_string()));
         assert!(!trie.starts_with("anything".to_string()));
     }
+
+    #[test]
+    fn test_remove_prefix() {
+        let mut trie = Trie::new();
+        trie.insert("car".to_string());
+        trie.insert("card".to_string());
+        trie.insert("care".to_string());
+        trie.insert("dog".to_string());
+
+        assert_eq!(trie.remove_prefix("car".to_string()), 3);
+        assert!(!trie.search("car".to_string()));
+        assert!(!trie.search("card".to_string()));
+        assert!(!trie.search("care".to_string()));
+        assert!(trie.search("dog".to_string()));
+
+        // Nothing left under a missing prefix
+        assert_eq!(trie.remove_prefix("car".to_string()), 0);
+        assert_eq!(trie.remove_prefix("".to_string()), 1);
+        assert!(!trie.search("dog".to_string()));
+    }
+
+    #[test]
+    fn test_top_completions() {
+        let mut trie = Trie::new();
+        trie.insert_weighted("apple".to_string(), 5);
+        trie.insert_weighted("app".to_string(), 10);
+        trie.insert_weighted("apply".to_string(), 2);
+
+        assert_eq!(
+            trie.top_completions("app".to_string(), 2),
+            vec![("app".to_string(), 10), ("apple".to_string(), 5)]
+        );
+
+        // Weights accumulate and ties fall back to alphabetical order
+        trie.insert_weighted("apply".to_string(), 3);
+        assert_eq!(
+            trie.top_completions("appl".to_string(), 5),
+            vec![("apple".to_string(), 5), ("apply".to_string(), 5)]
+        );
+        assert!(trie.top_completions("b".to_string(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_compact_trie_matches_array_trie() {
+        let words = ["apple", "application", "apply", "banana", "band", "zebra"];
+        let mut trie = Trie::new();
+        let mut compact = Trie::new_compact();
+        for word in words {
+            trie.insert(word.to_string());
+            compact.insert(word.to_string());
+        }
+
+        for query in ["apple", "app", "apply", "ban", "band", "zebra", "zoo", ""] {
+            assert_eq!(
+                trie.search(query.to_string()),
+                compact.search(query.to_string())
+            );
+            assert_eq!(
+                trie.starts_with(query.to_string()),
+                compact.starts_with(query.to_string())
+            );
+        }
+
+        assert_eq!(compact.remove_prefix("ban".to_string()), 2);
+        assert!(!compact.search("band".to_string()));
+        assert!(compact.search("zebra".to_string()));
+        assert_eq!(trie.remove_prefix("ban".to_string()), 2);
+
+        // Same shape, and the compact nodes take less memory even counting
+        // the heap slots each child Vec has reserved
+        let nodes = trie.node_count();
+        assert_eq!(nodes, compact.node_count());
+        fn compact_bytes(node: &CompactTrieNode) -> usize {
+            let slots =
+                node.children.nodes.capacity() * std::mem::size_of::<Box<CompactTrieNode>>();
+            let below: usize = node
+                .children
+                .iter()
+                .map(|(_, child)| compact_bytes(child))
+                .sum();
+            std::mem::size_of::<CompactTrieNode>() + slots + below
+        }
+        let array_bytes = nodes * std::mem::size_of::<TrieNode>();
+        assert!(compact_bytes(&compact.root) < array_bytes);
+    }
+
+    #[test]
+    fn test_try_insert_rejects_invalid_chars() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.try_insert("App1e"), Err(TrieError::InvalidChar('A')));
+        assert_eq!(trie.try_insert("app1e"), Err(TrieError::InvalidChar('1')));
+        assert_eq!(trie.try_insert("apple"), Ok(()));
+
+        // A rejected word leaves nothing behind
+        assert!(trie.search("apple".to_string()));
+        assert!(!trie.starts_with("app1".to_string()));
+        assert_eq!(trie.node_count(), 6);
+
+        // The infallible methods skip gracefully instead of panicking
+        trie.insert("App1e".to_string());
+        trie.insert("{".to_string());
+        assert!(!trie.search("App1e".to_string()));
+        assert!(!trie.starts_with("Z".to_string()));
+        assert!(!trie.search("caf\u{e9}".to_string()));
+        assert_eq!(trie.remove_prefix("A".to_string()), 0);
+        assert_eq!(trie.node_count(), 6);
+    }
+
+    #[test]
+    fn test_words_of_length() {
+        let mut trie = Trie::new();
+        for word in [
+            "apple", "app", "crane", "cranes", "zebra", "bread", "a", "apply",
+        ] {
+            trie.insert(word.to_string());
+        }
+
+        assert_eq!(
+            trie.words_of_length(5),
+            vec!["apple", "apply", "bread", "crane", "zebra"]
+        );
+        assert_eq!(trie.words_of_length(1), vec!["a"]);
+        assert!(trie.words_of_length(4).is_empty());
+        assert!(trie.words_of_length(0).is_empty());
+    }
+
+    #[test]
+    fn test_search_glob() {
+        let mut trie = Trie::new();
+        trie.insert("abc".to_string());
+        trie.insert("abd".to_string());
+        trie.insert("axbyc".to_string());
+
+        assert!(trie.search_glob("a*c".to_string()));
+        assert!(trie.search_glob("a?c".to_string()));
+        assert!(!trie.search_glob("a*z".to_string()));
+
+        // '*' may match nothing, '?' must match exactly one letter
+        assert!(trie.search_glob("abc*".to_string()));
+        assert!(trie.search_glob("*".to_string()));
+        assert!(trie.search_glob("a**b*c".to_string()));
+        assert!(trie.search_glob("ab?".to_string()));
+        assert!(!trie.search_glob("abc?".to_string()));
+        assert!(!trie.search_glob("????".to_string()));
+        assert!(trie.search_glob("?????".to_string()));
+        assert!(!trie.search_glob("".to_string()));
+    }
+
+    #[test]
+    fn test_recent_completions() {
+        let mut trie = Trie::new();
+        for word in ["car", "cart", "cat", "dog", "care"] {
+            trie.insert(word.to_string());
+        }
+
+        assert_eq!(
+            trie.recent_completions("ca".to_string(), 3),
+            vec!["care", "cat", "cart"]
+        );
+
+        // Re-inserting moves a word to the front, weighted inserts count too
+        trie.insert("car".to_string());
+        trie.insert_weighted("cab".to_string(), 1);
+        assert_eq!(
+            trie.recent_completions("car".to_string(), 10),
+            vec!["car", "care", "cart"]
+        );
+        assert_eq!(
+            trie.recent_completions("c".to_string(), 2),
+            vec!["cab", "car"]
+        );
+        assert!(trie.recent_completions("x".to_string(), 3).is_empty());
+        assert!(trie.recent_completions("c".to_string(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+        assert_eq!(edit_distance("recieve", "receive"), 2);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let mut trie = Trie::new();
+        for word in ["receive", "recieve", "recipe", "recital"] {
+            trie.insert(word.to_string());
+        }
+
+        // The stored misspelling matches exactly, then the longest shared prefix
+        assert_eq!(
+            trie.suggest("recieve".to_string(), 2),
+            vec!["recieve", "recipe"]
+        );
+
+        // Without the exact word, equal prefixes fall back to edit distance
+        assert_eq!(trie.remove_prefix("recie".to_string()), 1);
+        assert_eq!(
+            trie.suggest("recieve".to_string(), 3),
+            vec!["recipe", "recital", "receive"]
+        );
+        assert_eq!(trie.suggest("recepe".to_string(), 1), vec!["receive"]);
+        assert!(trie.suggest("x".to_string(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_matches_full_ranking() {
+        let words = [
+            "car", "card", "care", "cart", "cat", "dog", "dot", "do", "door", "a", "ab", "abc",
+        ];
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.to_string());
+        }
+        for query in ["ca", "cards", "dox", "b", "", "abd", "zzz", "doors"] {
+            let mut ranked: Vec<_> = words
+                .iter()
+                .map(|w| {
+                    let shared = w.chars().zip(query.chars()).take_while(|(a, b)| a == b);
+                    (shared.count(), edit_distance(w, query), w.to_string())
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+            for k in [1, 3, words.len() + 1] {
+                let expected: Vec<String> =
+                    ranked.iter().take(k).map(|(_, _, w)| w.clone()).collect();
+                assert_eq!(
+                    trie.suggest(query.to_string(), k),
+                    expected,
+                    "{query} k={k}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_words_in_insertion_order() {
+        let mut trie = Trie::new();
+        for word in ["pear", "apple", "zebra", "mango", "app"] {
+            trie.insert(word.to_string());
+        }
+        assert_eq!(
+            trie.words_in_insertion_order(),
+            vec!["pear", "apple", "zebra", "mango", "app"]
+        );
+
+        trie.insert("apple".to_string());
+        assert_eq!(
+            trie.words_in_insertion_order(),
+            vec!["pear", "zebra", "mango", "app", "apple"]
+        );
+        assert!(Trie::new().words_in_insertion_order().is_empty());
+    }
+
+    #[test]
+    fn test_longest_common_prefix() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.longest_common_prefix(), "");
+        for word in ["flower", "flow", "flight"] {
+            trie.insert(word.to_string());
+        }
+        assert_eq!(trie.longest_common_prefix(), "fl");
+
+        // A stored word stops the walk even without a fork
+        let mut trie = Trie::new_compact();
+        trie.insert("flow".to_string());
+        trie.insert("flower".to_string());
+        assert_eq!(trie.longest_common_prefix(), "flow");
+
+        // remove_prefix prunes emptied branches, so they never fork the walk
+        trie.insert("flask".to_string());
+        trie.remove_prefix("flo".to_string());
+        assert_eq!(trie.longest_common_prefix(), "flask");
+        trie.remove_prefix("flask".to_string());
+        assert_eq!(trie.longest_common_prefix(), "");
+    }
+
+    #[test]
+    fn test_longest_common_prefix_disjoint() {
+        let mut trie = Trie::new();
+        trie.insert("dog".to_string());
+        trie.insert("cat".to_string());
+        assert_eq!(trie.longest_common_prefix(), "");
+    }
+
+    #[test]
+    fn test_words_with_prefix_limited() {
+        let mut trie = Trie::new();
+        for word in [
+            "tea", "ten", "tab", "to", "toast", "ted", "tend", "t", "tax", "zoo",
+        ] {
+            trie.insert(word.to_string());
+        }
+
+        assert_eq!(
+            trie.words_with_prefix_limited("t".to_string(), 4),
+            vec!["t", "tab", "tax", "tea"]
+        );
+        assert_eq!(
+            trie.words_with_prefix_limited("te".to_string(), 10),
+            vec!["tea", "ted", "ten", "tend"]
+        );
+        assert!(trie
+            .words_with_prefix_limited("t".to_string(), 0)
+            .is_empty());
+        assert!(trie
+            .words_with_prefix_limited("q".to_string(), 3)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_node_pool_reuse() {
+        let mut trie = Trie::new();
+        trie.insert("keep".to_string());
+
+        for round in 0..50 {
+            for word in ["car", "card", "care", "cart"] {
+                trie.insert(word.to_string());
+            }
+            if round == 0 {
+                assert_eq!(trie.pooled_node_count(), 0);
+            }
+            assert!(trie.search("card".to_string()));
+            assert!(!trie.search("ca".to_string()));
+
+            // c, a, r, d, e, t come back to the pool and are reused next round
+            assert_eq!(trie.remove_prefix("c".to_string()), 4);
+            assert_eq!(trie.pooled_node_count(), 6);
+            assert!(!trie.starts_with("c".to_string()));
+        }
+
+        // Reused nodes come back clean
+        trie.insert("ca".to_string());
+        assert_eq!(trie.pooled_node_count(), 4);
+        assert!(trie.search("ca".to_string()));
+        assert!(!trie.search("c".to_string()));
+        assert_eq!(
+            trie.top_completions("c".to_string(), 5),
+            vec![("ca".to_string(), 0)]
+        );
+        assert!(trie.search("keep".to_string()));
+
+        // Clearing everything also feeds the pool, and the compact layout pools too
+        assert_eq!(trie.remove_prefix("".to_string()), 2);
+        assert_eq!(trie.node_count(), 1);
+        assert_eq!(trie.pooled_node_count(), 10);
+
+        let mut compact = Trie::new_compact();
+        compact.insert("abc".to_string());
+        compact.remove_prefix("a".to_string());
+        assert_eq!(compact.pooled_node_count(), 3);
+        compact.insert("xy".to_string());
+        assert_eq!(compact.pooled_node_count(), 1);
+        assert_eq!(compact.words_of_length(2), vec!["xy"]);
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let words = ["a", "apple", "apply", "banana", "band", "zebra", "zoo"];
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.to_string());
+        }
+
+        let bytes = trie.serialize_compact();
+        assert!(bytes.starts_with(b"TRIE\x01"));
+        // One u32 per node after the five header bytes
+        assert_eq!(bytes.len(), 5 + 4 * trie.node_count());
+
+        let restored = Trie::deserialize_compact(&bytes).unwrap();
+        assert_eq!(restored.node_count(), trie.node_count());
+        let mut restored_words: Vec<String> = (0..=6)
+            .flat_map(|len| restored.words_of_length(len))
+            .collect();
+        restored_words.sort();
+        assert_eq!(restored_words, words);
+        assert!(!restored.search("app".to_string()));
+
+        // The compact layout serializes to the same bytes
+        let mut compact = Trie::new_compact();
+        for word in words {
+            compact.insert(word.to_string());
+        }
+        assert_eq!(compact.serialize_compact(), bytes);
+
+        let empty = Trie::deserialize_compact(&Trie::new().serialize_compact()).unwrap();
+        assert_eq!(empty.node_count(), 1);
+    }
+
+    #[test]
+    fn test_compact_rejects_bad_input() {
+        let mut trie = Trie::new();
+        trie.insert("cat".to_string());
+        let bytes = trie.serialize_compact();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            Trie::deserialize_compact(&bad_magic).err(),
+            Some(TrieError::BadHeader)
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 9;
+        assert_eq!(
+            Trie::deserialize_compact(&bad_version).err(),
+            Some(TrieError::UnsupportedVersion(9))
+        );
+
+        assert!(matches!(
+            Trie::deserialize_compact(&bytes[..bytes.len() - 1]),
+            Err(TrieError::Corrupt(_))
+        ));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Trie::deserialize_compact(&trailing),
+            Err(TrieError::Corrupt(_))
+        ));
+        let mut reserved = bytes;
+        reserved[8] |= 0x80;
+        assert!(matches!(
+            Trie::deserialize_compact(&reserved),
+            Err(TrieError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_prefixes_of() {
+        let mut trie = Trie::new();
+        for word in ["a", "ab", "abc", "x"] {
+            trie.insert(word.to_string());
+        }
+        assert_eq!(trie.prefixes_of("abcd".to_string()), vec!["a", "ab", "abc"]);
+        assert_eq!(trie.prefixes_of("abc".to_string()), vec!["a", "ab", "abc"]);
+        assert_eq!(trie.prefixes_of("aX".to_string()), vec!["a"]);
+        assert!(trie.prefixes_of("b".to_string()).is_empty());
+        assert!(trie.prefixes_of(String::new()).is_empty());
+    }
+
+    #[test]
+    fn test_completions_dedupe_repeated_inserts() {
+        let mut trie = Trie::new();
+        trie.insert("app".to_string());
+        trie.insert_weighted("app".to_string(), 2);
+        trie.insert("app".to_string());
+        trie.insert("apple".to_string());
+
+        assert_eq!(
+            trie.words_with_prefix("app".to_string()),
+            vec!["app", "apple"]
+        );
+        assert_eq!(
+            trie.top_completions("app".to_string(), 5),
+            vec![("app".to_string(), 2), ("apple".to_string(), 0)]
+        );
+        assert_eq!(
+            trie.recent_completions("app".to_string(), 5),
+            vec!["apple", "app"]
+        );
+        assert_eq!(
+            trie.words_with_prefix_limited("a".to_string(), 5),
+            vec!["app", "apple"]
+        );
+        assert!(trie.words_with_prefix("b".to_string()).is_empty());
+    }
+
+    #[test]
+    fn test_is_strict_prefix() {
+        let mut trie = Trie::new();
+        trie.insert("apple".to_string());
+        trie.insert("banana".to_string());
+        assert!(trie.is_strict_prefix("app".to_string()));
+        assert!(trie.is_strict_prefix(String::new()));
+        assert!(!trie.is_strict_prefix("apple".to_string()));
+        assert!(!trie.is_strict_prefix("xyz".to_string()));
+
+        trie.remove_prefix("apple".to_string());
+        assert!(!trie.is_strict_prefix("app".to_string()));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut left = Trie::new();
+        left.insert_weighted("apple".to_string(), 3);
+        left.insert("app".to_string());
+        left.insert("banana".to_string());
+        let mut right = Trie::new();
+        right.insert_weighted("apple".to_string(), 2);
+        right.insert("apply".to_string());
+        right.insert("cherry".to_string());
+
+        left.merge(right);
+        for word in ["app", "apple", "apply", "banana", "cherry"] {
+            assert!(left.search(word.to_string()), "{} missing", word);
+        }
+        assert!(!left.search("ap".to_string()));
+        assert_eq!(
+            left.top_completions("apple".to_string(), 1),
+            vec![("apple".to_string(), 5)]
+        );
+        // Merged words count as inserted after the existing ones
+        assert_eq!(
+            left.words_in_insertion_order(),
+            vec!["app", "banana", "apple", "apply", "cherry"]
+        );
+
+        // New inserts are still newer than everything merged in
+        left.insert("avocado".to_string());
+        assert_eq!(left.recent_completions("a".to_string(), 1), vec!["avocado"]);
+
+        let mut compact = Trie::new_compact();
+        compact.insert("zoo".to_string());
+        let mut other = Trie::new_compact();
+        other.insert("zebra".to_string());
+        compact.merge(other);
+        assert!(compact.search("zoo".to_string()) && compact.search("zebra".to_string()));
+    }
+
+    #[test]
+    fn test_word_count() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.word_count(), 0);
+        for word in ["car", "card", "care", "cat", "dog", "car"] {
+            trie.insert(word.to_string());
+        }
+        // Re-inserting "car" does not add a terminal
+        assert_eq!(trie.word_count(), 5);
+
+        assert_eq!(trie.remove_prefix("card".to_string()), 1);
+        assert_eq!(trie.word_count(), 4);
+        assert_eq!(trie.remove_prefix("ca".to_string()), 3);
+        assert_eq!(trie.word_count(), 1);
+
+        // Pooled nodes come back clean, without stale terminal flags
+        trie.insert("cab".to_string());
+        assert_eq!(trie.word_count(), 2);
+        assert_eq!(trie.remove_prefix(String::new()), 2);
+        assert_eq!(trie.word_count(), 0);
+    }
+
+    #[test]
+    fn test_remove_prefix_prunes_empty_ancestors() {
+        let mut trie = Trie::new();
+        for word in ["car", "card", "care", "dog"] {
+            trie.insert(word.to_string());
+        }
+        assert_eq!(trie.node_count(), 9);
+        assert_eq!(trie.remove_prefix("car".to_string()), 3);
+        // "c" and "a" held nothing but the removed branch, so they go too
+        assert!(!trie.starts_with("ca".to_string()));
+        assert!(!trie.starts_with("c".to_string()));
+        assert_eq!(trie.node_count(), 4);
+        assert!(trie.search("dog".to_string()));
+
+        // A word on the path stops the pruning
+        trie.insert("do".to_string());
+        assert_eq!(trie.remove_prefix("dog".to_string()), 1);
+        assert!(trie.search("do".to_string()));
+        assert_eq!(trie.node_count(), 3);
+    }
 }
@@ -1,28 +1,70 @@
 use std::{
+    cell::RefCell,
     cmp::Ordering,
-    collections::{BinaryHeap, HashMap},
-    time::{SystemTime, UNIX_EPOCH},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 struct Tweet {
     user_id: i32,
     tweet_id: i32,
     timestamp: u64, // Using u64 for easier comparison
+    // A retweet reuses the original tweet_id under the retweeter's user_id
+    is_retweet: bool,
+    // Lowercased tags such as "#rust", taken from the text at post time
+    hashtags: Vec<String>,
 }
 
-impl Tweet {
-    fn new(user_id: i32, tweet_id: i32) -> Self {
-        // Get current time as milliseconds since UNIX epoch
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
+// Every "#word" in `text`, lowercased, without trailing punctuation and
+// without repeats
+#[allow(dead_code)]
+fn extract_hashtags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let Some(rest) = word.strip_prefix('#') else {
+            continue;
+        };
+        let tag: String = rest
+            .chars()
+            .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
+            .collect();
+        if tag.is_empty() {
+            continue;
+        }
+        let tag = normalize_hashtag(&tag);
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
 
+// "Rust" and "#rust" name the same tag
+#[allow(dead_code)]
+fn normalize_hashtag(tag: &str) -> String {
+    format!("#{}", tag.trim_start_matches('#').to_lowercase())
+}
+
+// Get current time as milliseconds since UNIX epoch
+#[allow(dead_code)]
+fn system_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[allow(dead_code)]
+impl Tweet {
+    fn with_timestamp(user_id: i32, tweet_id: i32, timestamp: u64) -> Self {
         Self {
             user_id,
             tweet_id,
             timestamp,
+            is_retweet: false,
+            hashtags: Vec::new(),
         }
     }
 }
@@ -30,11 +72,11 @@ impl Tweet {
 // For proper ordering in BinaryHeap (newest tweets first)
 impl Ord for Tweet {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Ordering by timestamp (descending) and then by tweet_id (descending)
+        // BinaryHeap is a max-heap, so the newest tweet must compare greatest:
+        // order by timestamp and then by tweet_id, both ascending
         self.timestamp
             .cmp(&other.timestamp)
-            .reverse()
-            .then_with(|| self.tweet_id.cmp(&other.tweet_id).reverse())
+            .then_with(|| self.tweet_id.cmp(&other.tweet_id))
     }
 }
 
@@ -52,67 +94,318 @@ impl PartialEq for Tweet {
 
 impl Eq for Tweet {}
 
+#[allow(dead_code)]
 type UserId = i32;
 
+#[allow(dead_code)]
 struct Twitter {
     tweets: HashMap<UserId, Vec<Tweet>>,
     followees: HashMap<UserId, Vec<UserId>>,
     max_news_feed_size: usize,
+    rate_limit: Option<RateLimit>,
+    // Timestamps of each user's posts inside the current rate-limit window
+    recent_posts: HashMap<UserId, VecDeque<u64>>,
+    clock: Box<dyn FnMut() -> u64>,
+    // Newest timestamp handed out so far, used to keep post_tweet monotonic
+    last_timestamp: u64,
+    // Users who liked each tweet, keyed by tweet id
+    likes: HashMap<i32, HashSet<UserId>>,
+    // Last get_news_feed result per user. get_news_feed only borrows self,
+    // so the cache sits in a RefCell; every write that can change a feed
+    // drops the affected entries.
+    feed_cache: RefCell<HashMap<UserId, Vec<i32>>>,
+    // Tags each user follows; matching tweets from anyone join their feed
+    followed_hashtags: HashMap<UserId, HashSet<String>>,
 }
 
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+struct RateLimit {
+    max_per_window: usize,
+    window_millis: u64,
+}
+
+#[allow(dead_code)]
 impl Twitter {
     fn new() -> Self {
         Self {
             tweets: HashMap::new(),
             followees: HashMap::new(),
             max_news_feed_size: 10, // News feed size limit is maintained
+            rate_limit: None,
+            recent_posts: HashMap::new(),
+            clock: Box::new(system_millis),
+            last_timestamp: 0,
+            likes: HashMap::new(),
+            feed_cache: RefCell::new(HashMap::new()),
+            followed_hashtags: HashMap::new(),
         }
     }
 
-    fn post_tweet(&mut self, user_id: i32, tweet_id: i32) {
-        let new_tweet = Tweet::new(user_id, tweet_id);
+    // Read post_tweet timestamps from `clock` instead of the system time
+    fn with_clock(clock: impl FnMut() -> u64 + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            ..Self::new()
+        }
+    }
+
+    // Each user may post at most `max_per_window` tweets in any sliding `window`
+    fn with_rate_limit(max_per_window: usize, window: Duration) -> Self {
+        Self {
+            rate_limit: Some(RateLimit {
+                max_per_window,
+                window_millis: window.as_millis() as u64,
+            }),
+            ..Self::new()
+        }
+    }
+
+    // Returns false if the post was dropped by the rate limiter
+    fn post_tweet(&mut self, user_id: i32, tweet_id: i32) -> bool {
+        let timestamp = self.next_timestamp();
+        self.add_tweet(Tweet::with_timestamp(user_id, tweet_id, timestamp))
+    }
+
+    // Post each id in order through post_tweet, so every one gets a later
+    // timestamp than the one before even within the same millisecond.
+    // Returns how many made it past the rate limiter.
+    fn post_tweets(&mut self, user_id: i32, tweet_ids: &[i32]) -> usize {
+        let mut posted = 0;
+        for &tweet_id in tweet_ids {
+            if self.post_tweet(user_id, tweet_id) {
+                posted += 1;
+            }
+        }
+        posted
+    }
+
+    // The clock may jump backwards (e.g. an NTP adjustment), so never hand
+    // out a timestamp at or before the last one
+    fn next_timestamp(&mut self) -> u64 {
+        let now = (self.clock)();
+        now.max(self.last_timestamp + 1)
+    }
+
+    // Post with an explicit timestamp instead of reading the system clock
+    fn post_tweet_at(&mut self, user_id: i32, tweet_id: i32, timestamp: u64) -> bool {
+        self.add_tweet(Tweet::with_timestamp(user_id, tweet_id, timestamp))
+    }
+
+    // Like post_tweet, but the text is kept for its hashtags
+    fn post_tweet_with_text(&mut self, user_id: i32, tweet_id: i32, text: &str) -> bool {
+        let timestamp = self.next_timestamp();
+        self.add_tweet(Tweet {
+            hashtags: extract_hashtags(text),
+            ..Tweet::with_timestamp(user_id, tweet_id, timestamp)
+        })
+    }
+
+    // Repost an existing tweet to the user's followers; returns false if no
+    // such tweet exists or the post was dropped by the rate limiter
+    fn retweet(&mut self, user_id: i32, tweet_id: i32) -> bool {
+        let exists = self
+            .tweets
+            .values()
+            .flatten()
+            .any(|tweet| tweet.tweet_id == tweet_id && !tweet.is_retweet);
+        if !exists {
+            return false;
+        }
+
+        let timestamp = self.next_timestamp();
+        self.add_tweet(Tweet {
+            is_retweet: true,
+            ..Tweet::with_timestamp(user_id, tweet_id, timestamp)
+        })
+    }
+
+    fn add_tweet(&mut self, new_tweet: Tweet) -> bool {
+        if !self.allow_post(new_tweet.user_id, new_tweet.timestamp) {
+            return false;
+        }
+        self.last_timestamp = self.last_timestamp.max(new_tweet.timestamp);
+        self.invalidate_feeds_showing(new_tweet.user_id);
+        let cache = self.feed_cache.get_mut();
+        for (user, tags) in &self.followed_hashtags {
+            if new_tweet.hashtags.iter().any(|tag| tags.contains(tag)) {
+                cache.remove(user);
+            }
+        }
 
         // Get or create the user's tweet list
-        let user_tweets = self.tweets.entry(user_id).or_insert_with(Vec::new);
+        let user_tweets = self.tweets.entry(new_tweet.user_id).or_default();
 
         // Add the new tweet
         user_tweets.push(new_tweet);
 
-        // Sort by timestamp (newest first)
-        user_tweets.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        // Sort newest first, ties to the larger id, so feeds can merge the
+        // per-user lists without re-sorting
+        user_tweets.sort_by(|a, b| b.cmp(a));
+        true
+    }
+
+    // Record a post at `now` if the user still has room in the window
+    fn allow_post(&mut self, user_id: i32, now: u64) -> bool {
+        let Some(limit) = self.rate_limit else {
+            return true;
+        };
+
+        let recent = self.recent_posts.entry(user_id).or_default();
+        // Forget posts that have slid out of the window
+        while let Some(&oldest) = recent.front() {
+            if oldest + limit.window_millis > now {
+                break;
+            }
+            recent.pop_front();
+        }
+
+        if recent.len() >= limit.max_per_window {
+            return false;
+        }
+        recent.push_back(now);
+        true
     }
 
     fn get_news_feed(&self, user_id: i32) -> Vec<i32> {
-        let mut all_tweets = BinaryHeap::new();
+        if let Some(feed) = self.feed_cache.borrow().get(&user_id) {
+            return feed.clone();
+        }
+        let feed: Vec<i32> = self.feed_iter(user_id).collect();
+        self.feed_cache.borrow_mut().insert(user_id, feed.clone());
+        feed
+    }
+
+    // Whether `tweet_id` is in get_news_feed(user_id), stopping as soon as
+    // it turns up or the feed limit is reached
+    fn feed_contains(&self, user_id: i32, tweet_id: i32) -> bool {
+        if let Some(feed) = self.feed_cache.borrow().get(&user_id) {
+            return feed.contains(&tweet_id);
+        }
+        self.feed_iter(user_id).any(|id| id == tweet_id)
+    }
+
+    // The same ids as get_news_feed, newest first. Skips the feed cache.
+    fn feed_iter(&self, user_id: i32) -> impl Iterator<Item = i32> + '_ {
+        self.feed_iter_where(user_id, |_| true)
+    }
+
+    // A post by `user_id` shows up in their own feed and every follower's
+    fn invalidate_feeds_showing(&mut self, user_id: i32) {
+        let cache = self.feed_cache.get_mut();
+        cache.remove(&user_id);
+        for (follower, followees) in &self.followees {
+            if followees.contains(&user_id) {
+                cache.remove(follower);
+            }
+        }
+    }
+
+    // Same as get_news_feed, but only tweets posted at or after `since_millis`
+    fn get_news_feed_since(&self, user_id: i32, since_millis: u64) -> Vec<i32> {
+        self.feed_iter_where(user_id, move |tweet| tweet.timestamp >= since_millis)
+            .collect()
+    }
+
+    // Feed ids from the tweets passing `keep`, merged lazily from the
+    // feed's sources: a tweet reachable through several retweets only
+    // shows up once, at its newest occurrence
+    fn feed_iter_where<'a>(
+        &'a self,
+        user_id: i32,
+        keep: impl Fn(&Tweet) -> bool + Copy + 'a,
+    ) -> impl Iterator<Item = i32> + 'a {
+        let sources = self.feed_sources(user_id).into_iter().map(move |source| {
+            source
+                .filter(move |tweet| keep(tweet))
+                .map(|tweet| (tweet.tweet_id, tweet.timestamp))
+        });
+        let mut seen = HashSet::new();
+        merge_feed_iters(sources)
+            .filter(move |&tweet_id| seen.insert(tweet_id))
+            .take(self.max_news_feed_size)
+    }
+
+    // Every tweet that may show up in a user's feed, in no particular order
+    fn feed_candidates(&self, user_id: i32) -> Vec<&Tweet> {
+        self.feed_sources(user_id).into_iter().flatten().collect()
+    }
+
+    // The tweet lists a user's feed draws from, each newest first like the
+    // per-user Vecs they walk
+    fn feed_sources(&self, user_id: i32) -> Vec<Box<dyn Iterator<Item = &Tweet> + '_>> {
+        let mut sources: Vec<Box<dyn Iterator<Item = &Tweet> + '_>> = Vec::new();
 
         // Add user's own tweets
         if let Some(user_tweets) = self.tweets.get(&user_id) {
-            for tweet in user_tweets {
-                all_tweets.push(tweet.clone());
-            }
+            sources.push(Box::new(user_tweets.iter()));
         }
 
         // Add followees' tweets
         if let Some(followees) = self.followees.get(&user_id) {
-            for &followee_id in followees {
-                if let Some(followee_tweets) = self.tweets.get(&followee_id) {
-                    for tweet in followee_tweets {
-                        all_tweets.push(tweet.clone());
-                    }
+            for followee_id in followees {
+                if let Some(followee_tweets) = self.tweets.get(followee_id) {
+                    sources.push(Box::new(followee_tweets.iter()));
                 }
             }
         }
 
-        // Extract top tweets (most recent)
-        let mut news_feed = Vec::new();
-        while let Some(tweet) = all_tweets.pop() {
-            news_feed.push(tweet.tweet_id);
-            if news_feed.len() >= self.max_news_feed_size {
-                break;
+        // Add anyone's tweets carrying a followed tag; a tweet that is
+        // already in via a followee is dropped later by tweet id
+        if let Some(tags) = self.followed_hashtags.get(&user_id) {
+            for user_tweets in self.tweets.values() {
+                sources.push(Box::new(user_tweets.iter().filter(move |tweet| {
+                    tweet.hashtags.iter().any(|tag| tags.contains(tag))
+                })));
             }
         }
 
-        news_feed
+        sources
+    }
+
+    // Feed ranked by like count instead of recency; ties go to the newer tweet
+    fn get_top_feed(&self, user_id: i32, limit: usize) -> Vec<i32> {
+        let mut candidates = self.feed_candidates(user_id);
+        candidates.sort_by(|a, b| {
+            self.like_count(b.tweet_id)
+                .cmp(&self.like_count(a.tweet_id))
+                .then_with(|| b.cmp(a))
+        });
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|tweet| seen.insert(tweet.tweet_id))
+            .take(limit)
+            .map(|tweet| tweet.tweet_id)
+            .collect()
+    }
+
+    // Liking is idempotent: a user counts at most once per tweet
+    fn like_tweet(&mut self, user_id: i32, tweet_id: i32) {
+        self.likes.entry(tweet_id).or_default().insert(user_id);
+    }
+
+    fn unlike_tweet(&mut self, user_id: i32, tweet_id: i32) {
+        if let Some(likers) = self.likes.get_mut(&tweet_id) {
+            likers.remove(&user_id);
+        }
+    }
+
+    // Whether `user_id` posted `tweet_id` themselves; retweets don't count
+    fn tweet_exists(&self, user_id: i32, tweet_id: i32) -> bool {
+        self.get_tweet_timestamp(user_id, tweet_id).is_some()
+    }
+
+    fn get_tweet_timestamp(&self, user_id: i32, tweet_id: i32) -> Option<u64> {
+        self.tweets
+            .get(&user_id)?
+            .iter()
+            .find(|tweet| tweet.tweet_id == tweet_id && !tweet.is_retweet)
+            .map(|tweet| tweet.timestamp)
+    }
+
+    fn like_count(&self, tweet_id: i32) -> usize {
+        self.likes.get(&tweet_id).map_or(0, |likers| likers.len())
     }
 
     fn follow(&mut self, follower_id: i32, followee_id: i32) {
@@ -121,8 +414,10 @@ impl Twitter {
             return;
         }
 
+        self.feed_cache.get_mut().remove(&follower_id);
+
         // Get or create the user's followee list
-        let followees = self.followees.entry(follower_id).or_insert_with(Vec::new);
+        let followees = self.followees.entry(follower_id).or_default();
 
         // Add followee if not already following
         if !followees.contains(&followee_id) {
@@ -131,6 +426,7 @@ impl Twitter {
     }
 
     fn unfollow(&mut self, follower_id: i32, followee_id: i32) {
+        self.feed_cache.get_mut().remove(&follower_id);
         if let Some(followees) = self.followees.get_mut(&follower_id) {
             // Remove the followee
             if let Some(pos) = followees.iter().position(|&id| id == followee_id) {
@@ -141,6 +437,22 @@ impl Twitter {
 
     // Additional utility functions
 
+    // `tag` may be given with or without the leading '#'
+    fn follow_hashtag(&mut self, user_id: i32, tag: String) {
+        self.feed_cache.get_mut().remove(&user_id);
+        self.followed_hashtags
+            .entry(user_id)
+            .or_default()
+            .insert(normalize_hashtag(&tag));
+    }
+
+    fn unfollow_hashtag(&mut self, user_id: i32, tag: String) {
+        self.feed_cache.get_mut().remove(&user_id);
+        if let Some(tags) = self.followed_hashtags.get_mut(&user_id) {
+            tags.remove(&normalize_hashtag(&tag));
+        }
+    }
+
     fn is_following(&self, follower_id: i32, followee_id: i32) -> bool {
         if let Some(followees) = self.followees.get(&follower_id) {
             followees.contains(&followee_id)
@@ -168,6 +480,166 @@ impl Twitter {
             Vec::new()
         }
     }
+
+    // Every tweet as (user_id, tweet_id, timestamp), newest first,
+    // regardless of who follows whom
+    fn all_tweets_sorted(&self) -> Vec<(i32, i32, u64)> {
+        let mut all: Vec<&Tweet> = self.tweets.values().flatten().collect();
+        all.sort_by(|a, b| b.cmp(a));
+        all.into_iter()
+            .map(|tweet| (tweet.user_id, tweet.tweet_id, tweet.timestamp))
+            .collect()
+    }
+
+    // (user_id, tweet count) for the `top_n` most prolific posters,
+    // retweets included; ties go to the smaller user id
+    fn most_active_users(&self, top_n: usize) -> Vec<(i32, usize)> {
+        let mut counts: Vec<(i32, usize)> = self
+            .tweets
+            .iter()
+            .map(|(&user_id, tweets)| (user_id, tweets.len()))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(top_n);
+        counts
+    }
+
+    // How often `tweet_id` by `original_user_id` has been retweeted; 0 if
+    // that user never posted it
+    fn repost_count(&self, original_user_id: i32, tweet_id: i32) -> usize {
+        if !self.tweet_exists(original_user_id, tweet_id) {
+            return 0;
+        }
+        self.tweets
+            .values()
+            .flatten()
+            .filter(|tweet| tweet.is_retweet && tweet.tweet_id == tweet_id)
+            .count()
+    }
+
+    // (author, tweet_id, reposts) for the `top_n` most retweeted tweets;
+    // tweets never retweeted are left out and ties go to the smaller id
+    fn trending_tweets(&self, top_n: usize) -> Vec<(i32, i32, usize)> {
+        let mut reposts: HashMap<i32, usize> = HashMap::new();
+        for tweet in self.tweets.values().flatten() {
+            if tweet.is_retweet {
+                *reposts.entry(tweet.tweet_id).or_default() += 1;
+            }
+        }
+
+        let mut trending: Vec<(i32, i32, usize)> = self
+            .tweets
+            .values()
+            .flatten()
+            .filter(|tweet| !tweet.is_retweet)
+            .filter_map(|tweet| {
+                let count = *reposts.get(&tweet.tweet_id)?;
+                Some((tweet.user_id, tweet.tweet_id, count))
+            })
+            .collect();
+        trending.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.cmp(&b.1)));
+        trending.truncate(top_n);
+        trending
+    }
+
+    // Graphviz DOT text with one edge per follow, e.g. for `dot -Tpng`.
+    // Users without any follows in either direction are left out.
+    fn export_follow_graph_dot(&self) -> String {
+        let mut edges: Vec<(UserId, UserId)> = self
+            .followees
+            .iter()
+            .flat_map(|(&follower, followees)| followees.iter().map(move |&f| (follower, f)))
+            .collect();
+        edges.sort_unstable();
+
+        let mut dot = String::from("digraph follows {\n");
+        for (follower, followee) in edges {
+            dot.push_str(&format!("    {} -> {};\n", follower, followee));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn delete_account(&mut self, user_id: i32) {
+        // The deleted tweets may sit in anyone's feed
+        self.feed_cache.get_mut().clear();
+
+        // Drop the user's tweets and their own followee list
+        if let Some(tweets) = self.tweets.remove(&user_id) {
+            // Likes belong to the original tweet, not to a retweet of it
+            let originals: HashSet<i32> = tweets
+                .iter()
+                .filter(|tweet| !tweet.is_retweet)
+                .map(|tweet| tweet.tweet_id)
+                .collect();
+            for tweet_id in &originals {
+                self.likes.remove(tweet_id);
+            }
+            // Other users' retweets of those tweets go with them
+            for user_tweets in self.tweets.values_mut() {
+                user_tweets
+                    .retain(|tweet| !(tweet.is_retweet && originals.contains(&tweet.tweet_id)));
+            }
+        }
+        self.followees.remove(&user_id);
+        self.followed_hashtags.remove(&user_id);
+        self.recent_posts.remove(&user_id);
+        for likers in self.likes.values_mut() {
+            likers.remove(&user_id);
+        }
+
+        // Nobody follows a deleted account
+        self.remove_from_all_feeds(user_id);
+    }
+
+    // Stop following everyone; the user's own tweets are untouched
+    fn unfollow_all(&mut self, user_id: i32) {
+        self.feed_cache.get_mut().remove(&user_id);
+        self.followees.remove(&user_id);
+    }
+
+    // Make every other user unfollow `user_id`, e.g. when suspending an
+    // account. Unlike delete_account, the user keeps their tweets and their
+    // own timeline.
+    fn remove_from_all_feeds(&mut self, user_id: i32) {
+        self.invalidate_feeds_showing(user_id);
+        for followees in self.followees.values_mut() {
+            followees.retain(|&id| id != user_id);
+        }
+    }
+}
+
+// K-way merge of feeds that are each already sorted newest-first as
+// (tweet_id, timestamp); ties go to the larger tweet_id, like Tweet's Ord
+#[allow(dead_code)]
+fn merge_sorted_feeds(feeds: &[Vec<(i32, u64)>], limit: usize) -> Vec<i32> {
+    merge_feed_iters(feeds.iter().map(|feed| feed.iter().copied()))
+        .take(limit)
+        .collect()
+}
+
+// The lazy core of merge_sorted_feeds, over any newest-first feeds. A
+// feed is only read as far as the merged output has been consumed.
+#[allow(dead_code)]
+fn merge_feed_iters<I>(feeds: impl IntoIterator<Item = I>) -> impl Iterator<Item = i32>
+where
+    I: Iterator<Item = (i32, u64)>,
+{
+    let mut feeds: Vec<I> = feeds.into_iter().collect();
+    // One cursor per feed: (timestamp, tweet_id, feed index)
+    let mut heap: BinaryHeap<(u64, i32, usize)> = feeds
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, feed)| feed.next().map(|(id, ts)| (ts, id, i)))
+        .collect();
+
+    std::iter::from_fn(move || {
+        let (_, tweet_id, i) = heap.pop()?;
+        if let Some((id, ts)) = feeds[i].next() {
+            heap.push((ts, id, i));
+        }
+        Some(tweet_id)
+    })
 }
 
 fn main() {}
@@ -388,4 +860,473 @@ mod tests {
         assert_eq!(feed[0], 100, "Most recent tweet should be first");
         assert_eq!(feed[9], 91, "News feed should have most recent 10 tweets");
     }
+
+    #[test]
+    fn test_delete_account() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet(1, 101);
+        twitter.post_tweet(2, 201);
+        twitter.post_tweet(3, 301);
+
+        // 1 -> 2 -> 3, and 3 -> 2
+        twitter.follow(1, 2);
+        twitter.follow(2, 3);
+        twitter.follow(3, 2);
+        assert_eq!(twitter.get_followers_count(2), 2);
+        assert!(twitter.get_news_feed(1).contains(&201));
+
+        twitter.delete_account(2);
+
+        assert_eq!(twitter.get_followers_count(2), 0);
+        assert_eq!(twitter.get_news_feed(1), vec![101]);
+        assert_eq!(twitter.get_news_feed(3), vec![301]);
+        assert!(twitter.get_user_tweets(2, None).is_empty());
+        assert!(twitter.get_news_feed(2).is_empty());
+        assert_eq!(twitter.get_followers_count(3), 0);
+    }
+
+    #[test]
+    fn test_delete_account_removes_retweets() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(2, 201, 2_000);
+        assert!(twitter.retweet(2, 101));
+        // 3 only sees 101 through 2's retweet
+        twitter.follow(3, 2);
+        assert_eq!(twitter.get_news_feed(3), vec![101, 201]);
+        assert_eq!(twitter.repost_count(1, 101), 1);
+
+        twitter.delete_account(1);
+
+        assert_eq!(twitter.get_news_feed(3), vec![201]);
+        assert!(!twitter.feed_contains(3, 101));
+        assert_eq!(twitter.get_user_tweets(2, None), vec![201]);
+        assert!(twitter.trending_tweets(5).is_empty());
+    }
+
+    #[test]
+    fn test_news_feed_since() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(2, 201, 2_000);
+        twitter.post_tweet_at(1, 102, 3_000);
+        twitter.post_tweet_at(2, 202, 4_000);
+        twitter.follow(1, 2);
+
+        assert_eq!(twitter.get_news_feed_since(1, 2_000), vec![202, 102, 201]);
+        assert_eq!(twitter.get_news_feed_since(1, 4_001), Vec::<i32>::new());
+        assert_eq!(twitter.get_news_feed_since(1, 0), twitter.get_news_feed(1));
+
+        // Still capped at the feed size
+        for i in 0..15 {
+            twitter.post_tweet_at(1, 1_000 + i, 5_000 + i as u64);
+        }
+        let feed = twitter.get_news_feed_since(1, 5_000);
+        assert_eq!(feed.len(), 10);
+        assert_eq!(feed[0], 1_014);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut twitter = Twitter::with_rate_limit(2, Duration::from_secs(1));
+        assert!(twitter.post_tweet_at(1, 101, 1_000));
+        assert!(twitter.post_tweet_at(1, 102, 1_200));
+        assert!(
+            !twitter.post_tweet_at(1, 103, 1_500),
+            "Third post in the window"
+        );
+        assert!(twitter.post_tweet_at(2, 201, 1_500), "Limits are per user");
+
+        // 101 has slid out of the window, so there is room for one more
+        assert!(twitter.post_tweet_at(1, 104, 2_000));
+        assert!(!twitter.post_tweet_at(1, 105, 2_100));
+
+        assert_eq!(twitter.get_user_tweets(1, None), vec![104, 102, 101]);
+    }
+
+    #[test]
+    fn test_no_rate_limit_by_default() {
+        let mut twitter = Twitter::new();
+        for i in 0..100 {
+            assert!(twitter.post_tweet_at(1, i, 1_000));
+        }
+        assert_eq!(twitter.get_user_tweets(1, None).len(), 100);
+    }
+
+    #[test]
+    fn test_timestamps_monotonic_under_clock_skew() {
+        // A clock that keeps going backwards
+        let mut now = 10_000;
+        let mut twitter = Twitter::with_clock(move || {
+            now -= 1_000;
+            now
+        });
+        for i in 1..=5 {
+            twitter.post_tweet(1, i);
+        }
+
+        let timestamps: Vec<u64> = twitter.tweets[&1]
+            .iter()
+            .rev()
+            .map(|t| t.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![9_000, 9_001, 9_002, 9_003, 9_004]);
+        assert_eq!(twitter.get_news_feed(1), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_all_tweets_sorted() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 100);
+        twitter.post_tweet_at(2, 201, 300);
+        twitter.post_tweet_at(3, 301, 200);
+        twitter.post_tweet_at(1, 102, 500);
+        twitter.post_tweet_at(3, 302, 400);
+
+        // Nobody follows anybody, the dump still has everything
+        assert_eq!(
+            twitter.all_tweets_sorted(),
+            vec![
+                (1, 102, 500),
+                (3, 302, 400),
+                (2, 201, 300),
+                (3, 301, 200),
+                (1, 101, 100),
+            ]
+        );
+        assert!(Twitter::new().all_tweets_sorted().is_empty());
+    }
+
+    #[test]
+    fn test_top_feed_ranks_by_likes() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(2, 201, 2_000);
+        twitter.post_tweet_at(2, 202, 3_000);
+        twitter.follow(1, 2);
+
+        // The oldest tweet is the most liked
+        twitter.like_tweet(3, 101);
+        twitter.like_tweet(4, 101);
+        twitter.like_tweet(3, 202);
+        twitter.like_tweet(3, 202); // liking twice counts once
+
+        assert_eq!(twitter.like_count(101), 2);
+        assert_eq!(twitter.like_count(202), 1);
+        assert_eq!(twitter.get_top_feed(1, 10), vec![101, 202, 201]);
+        assert_eq!(twitter.get_top_feed(1, 2), vec![101, 202]);
+        // The chronological feed is unaffected
+        assert_eq!(twitter.get_news_feed(1), vec![202, 201, 101]);
+
+        twitter.unlike_tweet(3, 101);
+        twitter.unlike_tweet(4, 101);
+        assert_eq!(twitter.get_top_feed(1, 10), vec![202, 201, 101]);
+    }
+
+    #[test]
+    fn test_feed_dedups_retweets() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(2, 201, 2_000);
+        assert!(twitter.retweet(2, 101));
+        assert!(twitter.retweet(3, 101));
+        assert!(!twitter.retweet(3, 999)); // no such tweet
+
+        twitter.follow(4, 2);
+        twitter.follow(4, 3);
+        assert_eq!(twitter.get_news_feed(4), vec![101, 201]);
+
+        // The author sees their own tweet once as well
+        twitter.follow(1, 2);
+        assert_eq!(twitter.get_news_feed(1), vec![101, 201]);
+        assert_eq!(twitter.get_top_feed(4, 10), vec![101, 201]);
+    }
+
+    #[test]
+    fn test_export_follow_graph_dot() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet(5, 1); // posting alone does not add a node
+        twitter.follow(1, 2);
+        twitter.follow(1, 3);
+        twitter.follow(3, 1);
+        twitter.follow(4, 2);
+        twitter.follow(4, 2);
+
+        let dot = twitter.export_follow_graph_dot();
+        assert!(dot.starts_with("digraph follows {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("1 -> 2;"));
+        assert!(dot.contains("1 -> 3;"));
+        assert!(dot.contains("3 -> 1;"));
+        assert!(dot.contains("4 -> 2;"));
+        assert_eq!(dot.matches("->").count(), 4);
+        assert!(!dot.contains('5'));
+
+        assert_eq!(
+            Twitter::new().export_follow_graph_dot(),
+            "digraph follows {\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_sorted_feeds() {
+        let feeds: [Vec<(i32, u64)>; 4] = [
+            vec![(1, 90), (2, 50), (3, 10)],
+            vec![(4, 80), (5, 50), (6, 40), (7, 5)],
+            vec![],
+            vec![(8, 100), (9, 20)],
+        ];
+
+        let mut naive: Vec<(i32, u64)> = feeds.iter().flatten().copied().collect();
+        naive.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+        let naive: Vec<i32> = naive.into_iter().map(|(id, _)| id).collect();
+
+        for limit in [0, 1, 4, 9, 20] {
+            let expected: Vec<i32> = naive.iter().copied().take(limit).collect();
+            assert_eq!(merge_sorted_feeds(&feeds, limit), expected);
+        }
+        assert_eq!(merge_sorted_feeds(&feeds, 3), vec![8, 1, 4]);
+        assert!(merge_sorted_feeds(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_most_active_users() {
+        let mut twitter = Twitter::new();
+        for tweet_id in 0..3 {
+            twitter.post_tweet(7, tweet_id);
+        }
+        for tweet_id in 10..15 {
+            twitter.post_tweet(2, tweet_id);
+        }
+        for tweet_id in 20..23 {
+            twitter.post_tweet(4, tweet_id);
+        }
+        twitter.post_tweet(9, 30);
+
+        assert_eq!(
+            twitter.most_active_users(10),
+            vec![(2, 5), (4, 3), (7, 3), (9, 1)]
+        );
+        assert_eq!(twitter.most_active_users(2), vec![(2, 5), (4, 3)]);
+        assert!(twitter.most_active_users(0).is_empty());
+    }
+
+    #[test]
+    fn test_feed_cache_invalidation() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.follow(2, 1);
+        assert_eq!(twitter.get_news_feed(2), vec![101]);
+        assert!(twitter.feed_cache.borrow().contains_key(&2));
+
+        // A new post by a followee reaches the cached follower
+        twitter.post_tweet_at(1, 102, 2_000);
+        assert!(!twitter.feed_cache.borrow().contains_key(&2));
+        assert_eq!(twitter.get_news_feed(2), vec![102, 101]);
+        assert_eq!(twitter.get_news_feed(1), vec![102, 101]);
+
+        // Unrelated posts leave the cache alone
+        twitter.post_tweet_at(3, 301, 3_000);
+        assert!(twitter.feed_cache.borrow().contains_key(&2));
+
+        twitter.follow(2, 3);
+        assert_eq!(twitter.get_news_feed(2), vec![301, 102, 101]);
+        twitter.unfollow(2, 1);
+        assert_eq!(twitter.get_news_feed(2), vec![301]);
+        twitter.retweet(3, 101);
+        assert_eq!(twitter.get_news_feed(2), vec![101, 301]);
+        twitter.delete_account(3);
+        assert!(twitter.get_news_feed(2).is_empty());
+    }
+
+    #[test]
+    fn test_unfollow_all() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(2, 201, 1_000);
+        twitter.post_tweet_at(3, 301, 2_000);
+        twitter.post_tweet_at(1, 101, 3_000);
+        twitter.follow(1, 2);
+        twitter.follow(1, 3);
+        twitter.follow(2, 1);
+        assert_eq!(twitter.get_news_feed(1), vec![101, 301, 201]);
+
+        twitter.unfollow_all(1);
+        assert!(!twitter.is_following(1, 2));
+        assert!(!twitter.is_following(1, 3));
+        assert_eq!(twitter.get_news_feed(1), vec![101]);
+        // Others still follow the user
+        assert!(twitter.is_following(2, 1));
+    }
+
+    #[test]
+    fn test_remove_from_all_feeds() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(2, 201, 2_000);
+        twitter.follow(2, 1);
+        twitter.follow(3, 1);
+        twitter.follow(1, 2);
+        assert_eq!(twitter.get_news_feed(3), vec![101]);
+
+        twitter.remove_from_all_feeds(1);
+        assert_eq!(twitter.get_followers_count(1), 0);
+        assert_eq!(twitter.get_news_feed(2), vec![201]);
+        assert!(twitter.get_news_feed(3).is_empty());
+
+        // The suspended user keeps their tweets and their own follows
+        assert_eq!(twitter.get_user_tweets(1, None), vec![101]);
+        assert_eq!(twitter.get_news_feed(1), vec![201, 101]);
+    }
+
+    #[test]
+    fn test_extract_hashtags() {
+        assert_eq!(
+            extract_hashtags("Learning #Rust today, #rust! #100DaysOfCode # #"),
+            vec!["#rust", "#100daysofcode"]
+        );
+        assert!(extract_hashtags("no tags here").is_empty());
+    }
+
+    #[test]
+    fn test_follow_hashtag() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_with_text(7, 701, "Shipping a crate #Rust");
+        twitter.post_tweet_with_text(8, 801, "#python tips");
+        assert!(twitter.get_news_feed(1).is_empty());
+
+        // No followed accounts, but the tag brings in a stranger's tweet
+        twitter.follow_hashtag(1, "#rust".to_string());
+        assert_eq!(twitter.get_news_feed(1), vec![701]);
+
+        // New tagged posts reach the cached feed
+        twitter.post_tweet_with_text(9, 901, "more #rust");
+        assert_eq!(twitter.get_news_feed(1), vec![901, 701]);
+
+        // A tweet reachable through a followee and a tag shows up once
+        twitter.follow(1, 7);
+        twitter.post_tweet(7, 702);
+        assert_eq!(twitter.get_news_feed(1), vec![702, 901, 701]);
+
+        twitter.unfollow_hashtag(1, "RUST".to_string());
+        assert_eq!(twitter.get_news_feed(1), vec![702, 701]);
+    }
+
+    #[test]
+    fn test_feed_contains() {
+        let mut twitter = Twitter::new();
+        for i in 0..12 {
+            twitter.post_tweet_at(2, 200 + i, 1_000 + i as u64);
+        }
+        twitter.follow(1, 2);
+
+        // The two oldest tweets are pushed out by the 10-tweet limit
+        assert!(twitter.feed_contains(1, 211));
+        assert!(twitter.feed_contains(1, 202));
+        assert!(!twitter.feed_contains(1, 201));
+        assert!(!twitter.feed_contains(1, 200));
+        assert!(!twitter.feed_contains(1, 999));
+        assert!(!twitter.feed_contains(3, 211));
+
+        // Same answers once the feed is cached
+        twitter.get_news_feed(1);
+        assert!(twitter.feed_contains(1, 202));
+        assert!(!twitter.feed_contains(1, 201));
+    }
+
+    #[test]
+    fn test_tweet_lookups() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.retweet(2, 101);
+
+        assert!(twitter.tweet_exists(1, 101));
+        assert_eq!(twitter.get_tweet_timestamp(1, 101), Some(1_000));
+        assert!(!twitter.tweet_exists(1, 102));
+        assert_eq!(twitter.get_tweet_timestamp(1, 102), None);
+        assert_eq!(twitter.get_tweet_timestamp(3, 101), None);
+        // A retweet is not the retweeter's own post
+        assert!(!twitter.tweet_exists(2, 101));
+    }
+
+    #[test]
+    fn test_post_tweets() {
+        let mut twitter = Twitter::new();
+        assert_eq!(twitter.post_tweets(1, &[1, 2, 3, 4, 5]), 5);
+        assert_eq!(twitter.get_news_feed(1), vec![5, 4, 3, 2, 1]);
+
+        let mut limited = Twitter::with_rate_limit(2, Duration::from_secs(60));
+        assert_eq!(limited.post_tweets(1, &[1, 2, 3]), 2);
+        assert_eq!(limited.get_news_feed(1), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_feed_iter() {
+        let mut twitter = Twitter::new();
+        for i in 0..500 {
+            twitter.post_tweet_at(2 + i % 5, 1_000 + i, 10_000 + i as u64);
+        }
+        for followee in 2..7 {
+            twitter.follow(1, followee);
+        }
+        twitter.retweet(3, 1_499);
+
+        let mut feed = twitter.feed_iter(1);
+        assert_eq!(
+            feed.by_ref().take(2).collect::<Vec<_>>(),
+            vec![1_499, 1_498]
+        );
+        // The rest is still there to pull, and the retweet is not repeated
+        assert_eq!(feed.next(), Some(1_497));
+        assert_eq!(
+            twitter.feed_iter(1).collect::<Vec<_>>(),
+            twitter.get_news_feed(1)
+        );
+        assert_eq!(twitter.feed_iter(99).next(), None);
+    }
+
+    #[test]
+    fn test_feed_iter_reads_only_what_it_yields() {
+        // Equal timestamps posted out of id order still merge like Tweet's Ord
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 5, 1_000);
+        twitter.post_tweet_at(1, 9, 1_000);
+        twitter.post_tweet_at(2, 7, 1_000);
+        twitter.post_tweet_at(2, 3, 500);
+        twitter.follow(1, 2);
+        assert_eq!(twitter.feed_iter(1).collect::<Vec<_>>(), vec![9, 7, 5, 3]);
+
+        // Taking two ids reads each feed's head plus one step per id
+        let reads = std::cell::Cell::new(0);
+        let feeds: Vec<Vec<(i32, u64)>> = (0..3)
+            .map(|f| (0..100).map(|i| (f * 100 + i, 10_000 - i as u64)).collect())
+            .collect();
+        let merged = merge_feed_iters(
+            feeds
+                .iter()
+                .map(|feed| feed.iter().copied().inspect(|_| reads.set(reads.get() + 1))),
+        );
+        assert_eq!(merged.take(2).collect::<Vec<_>>(), vec![200, 100]);
+        assert_eq!(reads.get(), 3 + 2);
+    }
+
+    #[test]
+    fn test_reposts_and_trending() {
+        let mut twitter = Twitter::new();
+        twitter.post_tweet_at(1, 101, 1_000);
+        twitter.post_tweet_at(1, 102, 2_000);
+        twitter.post_tweet_at(2, 201, 3_000);
+        twitter.post_tweet_at(2, 202, 4_000);
+        twitter.retweet(2, 101);
+        twitter.retweet(3, 101);
+        twitter.retweet(3, 201);
+
+        assert_eq!(twitter.repost_count(1, 101), 2);
+        assert_eq!(twitter.repost_count(2, 201), 1);
+        assert_eq!(twitter.repost_count(1, 102), 0);
+        // Right tweet, wrong author
+        assert_eq!(twitter.repost_count(2, 101), 0);
+
+        assert_eq!(twitter.trending_tweets(5), vec![(1, 101, 2), (2, 201, 1)]);
+        assert_eq!(twitter.trending_tweets(1), vec![(1, 101, 2)]);
+    }
 }
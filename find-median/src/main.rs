@@ -1,35 +1,600 @@
+use std::collections::VecDeque;
+
+// What find_median reports when the count is even and there are two
+// middle values
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MedianPolicy {
+    // Mean of the two middle values
+    Average,
+    Lower,
+    Upper,
+    // Linear interpolation at the fractional rank, as percentile(50) does.
+    // The median's rank is always a whole number or a half, so this only
+    // differs from Average for a t-digest's estimate.
+    Interpolated,
+}
+
 struct MedianFinder {
+    backend: Backend,
+    policy: MedianPolicy,
+    // (value, total weight) pairs from add_weighted, sorted by value with
+    // one entry per distinct value; separate from the add_num stream.
+    // Summed as u128 since many u64 weights can overflow a u64 total.
+    weighted: Vec<(i32, u128)>,
+    total_weight: u128,
+}
+
+// How the add_num stream is stored
+enum Backend {
+    Exact(Exact),
+    // Numbers are counted instead of kept
+    Histogram(Histogram),
+    // Only a random sample of the stream is kept
+    Reservoir(Reservoir),
+    // Numbers are folded into centroids instead of kept
+    TDigest(TDigest),
+}
+
+// Every number, sorted, and the same numbers in arrival order for
+// median_of_last_k
+#[derive(Default)]
+struct Exact {
+    nums: Vec<i32>,
+    arrivals: VecDeque<i32>,
+}
+
+impl Exact {
+    fn add(&mut self, num: i32) {
+        insert_sorted(&mut self.nums, num);
+        self.arrivals.push_back(num);
+    }
+
+    // Popping takes a value, not a particular arrival, so the oldest
+    // arrival of that value goes
+    fn forget_arrival(&mut self, num: i32) {
+        if let Some(pos) = self.arrivals.iter().position(|&arrival| arrival == num) {
+            self.arrivals.remove(pos);
+        }
+    }
+}
+
+fn insert_sorted(nums: &mut Vec<i32>, num: i32) {
+    // Insert number in the correct position to maintain sorted order
+    // Using binary search to find the insertion position
+    let pos = match nums.binary_search(&num) {
+        Ok(pos) => pos,
+        Err(pos) => pos, // If not found, returns where it would be inserted
+    };
+    nums.insert(pos, num);
+}
+
+// Reservoir sampling state (Algorithm R) with a small built-in RNG, so the
+// sample can be reproduced from a seed without pulling in a crate
+struct Reservoir {
+    // The sample, sorted
     nums: Vec<i32>,
+    size: usize,
+    seen: u64,
+    rng_state: u64,
+}
+
+impl Reservoir {
+    // splitmix64
+    fn next_random(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // The num-th number replaces a random sampled one with probability
+    // size / seen. `nums` is kept sorted, but any uniformly chosen victim
+    // keeps the sample uniform.
+    fn add(&mut self, num: i32) {
+        self.seen += 1;
+        if self.nums.len() >= self.size {
+            let victim = self.next_random() % self.seen;
+            if victim >= self.size as u64 {
+                return;
+            }
+            self.nums.remove(victim as usize);
+        }
+        insert_sorted(&mut self.nums, num);
+    }
+}
+
+// One counter per value in [min, max]
+struct Histogram {
+    min: i32,
+    counts: Vec<usize>,
+    total: usize,
+}
+
+impl Histogram {
+    fn new(min: i32, max: i32) -> Self {
+        assert!(min <= max, "histogram range {}..={} is empty", min, max);
+        Self {
+            min,
+            counts: vec![0; (max as i64 - min as i64 + 1) as usize],
+            total: 0,
+        }
+    }
+
+    fn value_at(&self, index: usize) -> i32 {
+        (self.min as i64 + index as i64) as i32
+    }
+
+    fn add(&mut self, num: i32) {
+        let max = self.value_at(self.counts.len() - 1);
+        let index = (num.clamp(self.min, max) as i64 - self.min as i64) as usize;
+        self.counts[index] += 1;
+        self.total += 1;
+    }
+
+    // Value at position `rank` of the sorted numbers
+    fn nth(&self, rank: usize) -> Option<i32> {
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen > rank {
+                return Some(self.value_at(index));
+            }
+        }
+        None
+    }
+
+    fn median(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        // Walk the buckets once, picking up both middle ranks on the way
+        let (lower_rank, upper_rank) = ((self.total - 1) / 2, self.total / 2);
+        let mut lower = None;
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if lower.is_none() && seen > lower_rank {
+                lower = Some(self.value_at(index));
+            }
+            if seen > upper_rank {
+                let upper = self.value_at(index);
+                return (lower.unwrap() as f64 + upper as f64) / 2.0;
+            }
+        }
+        unreachable!("total matches the bucket counts")
+    }
+
+    fn remove_at(&mut self, index: usize) -> i32 {
+        self.counts[index] -= 1;
+        self.total -= 1;
+        self.value_at(index)
+    }
+
+    fn pop_min(&mut self) -> Option<i32> {
+        let index = self.counts.iter().position(|&count| count > 0)?;
+        Some(self.remove_at(index))
+    }
+
+    fn pop_max(&mut self) -> Option<i32> {
+        let index = self.counts.iter().rposition(|&count| count > 0)?;
+        Some(self.remove_at(index))
+    }
+
+    // Every counted value in ascending order, leaving all buckets empty
+    fn drain(&mut self) -> Vec<i32> {
+        let mut nums = Vec::with_capacity(self.total);
+        for index in 0..self.counts.len() {
+            let count = std::mem::take(&mut self.counts[index]);
+            nums.extend(std::iter::repeat_n(self.value_at(index), count));
+        }
+        self.total = 0;
+        nums
+    }
+}
+
+// Larger keeps more centroids and gives tighter estimates
+const TDIGEST_COMPRESSION: f64 = 100.0;
+// Numbers buffered before they are merged into the centroids
+const TDIGEST_BUFFER: usize = 500;
+
+// Simplified merging t-digest: sorted (mean, count) centroids that may
+// grow large around the median but stay small near the tails, where a
+// percentile needs more resolution. Only a bounded number of centroids
+// plus the buffer are kept however long the stream runs.
+struct TDigest {
+    centroids: Vec<(f64, u64)>,
+    buffer: Vec<f64>,
+    total: u64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    fn new() -> Self {
+        Self {
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            total: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn add(&mut self, num: i32) {
+        let num = num as f64;
+        self.buffer.push(num);
+        self.total += 1;
+        self.min = self.min.min(num);
+        self.max = self.max.max(num);
+        if self.buffer.len() >= TDIGEST_BUFFER {
+            self.centroids = self.merged();
+            self.buffer.clear();
+        }
+    }
+
+    // The centroids with the buffer folded in. Neighbours are merged while
+    // the quantile range they cover spans at most one unit of the scale
+    // k(q) = compression / (2 * pi) * asin(2q - 1), which is steep near
+    // the tails and flat in the middle. The whole scale is `compression`
+    // units wide, so the centroid count is bounded by a small multiple of it.
+    fn merged(&self) -> Vec<(f64, u64)> {
+        let mut all = self.centroids.clone();
+        all.extend(self.buffer.iter().map(|&num| (num, 1)));
+        all.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let total = self.total as f64;
+        let mut merged = Vec::new();
+        let mut all = all.into_iter();
+        let Some(mut current) = all.next() else {
+            return merged;
+        };
+        let mut before = 0;
+        for next in all {
+            let count = current.1 + next.1;
+            let (q_start, q_end) = (before as f64 / total, (before + count) as f64 / total);
+            if scale(q_end) - scale(q_start) <= 1.0 {
+                let mean = current.0 + (next.0 - current.0) * next.1 as f64 / count as f64;
+                current = (mean, count);
+            } else {
+                merged.push(current);
+                before += current.1;
+                current = next;
+            }
+        }
+        merged.push(current);
+        merged
+    }
+
+    // Each centroid stands for its count of values centred on its mean;
+    // estimates between centres (and out to min/max) are linear
+    fn percentile(&self, p: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let centroids = self.merged();
+        let target = p / 100.0 * self.total as f64;
+
+        let mut prev = (0.0, self.min);
+        let mut before = 0.0;
+        for &(mean, count) in &centroids {
+            let centre = before + count as f64 / 2.0;
+            if target < centre {
+                return interpolate(prev, (centre, mean), target);
+            }
+            prev = (centre, mean);
+            before += count as f64;
+        }
+        interpolate(prev, (before, self.max), target)
+    }
+
+    fn len(&self) -> usize {
+        self.centroids.len() + self.buffer.len()
+    }
+}
+
+fn scale(q: f64) -> f64 {
+    TDIGEST_COMPRESSION / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin()
+}
+
+// Value at percentile `p` of `len` sorted numbers, where `nth` reads the
+// number at a rank, interpolating between neighbouring ranks
+fn rank_percentile(len: usize, p: f64, nth: impl Fn(usize) -> f64) -> f64 {
+    if len == 0 {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (len - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    nth(lower) + (nth(upper) - nth(lower)) * (rank - lower as f64)
+}
+
+// Value at `x` on the line through the (position, value) points `a` and `b`
+fn interpolate(a: (f64, f64), b: (f64, f64), x: f64) -> f64 {
+    if b.0 <= a.0 {
+        return b.1;
+    }
+    a.1 + (b.1 - a.1) * (x - a.0) / (b.0 - a.0)
 }
 
 impl MedianFinder {
     fn new() -> Self {
-        Self { nums: Vec::new() }
+        Self {
+            backend: Backend::Exact(Exact::default()),
+            policy: MedianPolicy::Average,
+            weighted: Vec::new(),
+            total_weight: 0,
+        }
+    }
+
+    fn with_policy(policy: MedianPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::new()
+        }
+    }
+
+    // Count values in [min, max] instead of sorting them: add_num is O(1)
+    // and find_median is one pass over max - min + 1 buckets. Values
+    // outside the range are clamped to the nearest end, so the median is
+    // only exact while every number fits the range.
+    fn with_histogram(min: i32, max: i32) -> Self {
+        Self {
+            backend: Backend::Histogram(Histogram::new(min, max)),
+            ..Self::new()
+        }
+    }
+
+    // Keep a uniform random sample of at most `size` numbers, so memory
+    // stays bounded on an endless stream; every median is then an estimate
+    // over the sample. Seeded from the system time.
+    fn with_reservoir(size: usize) -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::with_reservoir_seeded(size, seed)
+    }
+
+    // Approximate percentiles in bounded memory via a t-digest. Only
+    // estimates are available: pop_min and pop_max return None, the
+    // lower/upper medians are the estimate rounded, and drain just resets.
+    fn with_tdigest() -> Self {
+        Self {
+            backend: Backend::TDigest(TDigest::new()),
+            ..Self::new()
+        }
+    }
+
+    fn with_reservoir_seeded(size: usize, seed: u64) -> Self {
+        Self {
+            backend: Backend::Reservoir(Reservoir {
+                nums: Vec::new(),
+                size,
+                seen: 0,
+                rng_state: seed,
+            }),
+            ..Self::new()
+        }
     }
 
     fn add_num(&mut self, num: i32) {
-        // Insert number in the correct position to maintain sorted order
-        // Using binary search to find the insertion position
-        let pos = match self.nums.binary_search(&num) {
-            Ok(pos) => pos,
-            Err(pos) => pos, // If not found, returns where it would be inserted
-        };
-        self.nums.insert(pos, num);
+        match &mut self.backend {
+            Backend::Exact(exact) => exact.add(num),
+            Backend::Histogram(histogram) => histogram.add(num),
+            Backend::Reservoir(reservoir) => reservoir.add(num),
+            Backend::TDigest(tdigest) => tdigest.add(num),
+        }
+    }
+
+    fn add_num_sorted(&mut self, num: i32) {
+        match &mut self.backend {
+            Backend::Exact(exact) => {
+                // Caller guarantees the stream is ascending, so the new number
+                // belongs at the back and no search is needed
+                debug_assert!(
+                    exact.nums.last().is_none_or(|&last| last <= num),
+                    "add_num_sorted called with {} after a larger value",
+                    num
+                );
+                exact.nums.push(num);
+                exact.arrivals.push_back(num);
+            }
+            Backend::Histogram(histogram) => histogram.add(num),
+            // Sampling may replace any element, not just the last one
+            Backend::Reservoir(_) | Backend::TDigest(_) => self.add_num(num),
+        }
+    }
+
+    fn add_num_returning_median(&mut self, num: i32) -> f64 {
+        // Convenience for running medians: insert, then read the new median
+        self.add_num(num);
+        self.find_median()
     }
 
     fn find_median(&self) -> f64 {
-        let len = self.nums.len();
+        match self.policy {
+            MedianPolicy::Average => self.average_median(),
+            MedianPolicy::Lower => self.find_lower_median().map_or(0.0, |num| num as f64),
+            MedianPolicy::Upper => self.find_upper_median().map_or(0.0, |num| num as f64),
+            MedianPolicy::Interpolated => self.percentile(50.0),
+        }
+    }
+
+    fn average_median(&self) -> f64 {
+        let nums = match &self.backend {
+            Backend::Exact(Exact { nums, .. }) | Backend::Reservoir(Reservoir { nums, .. }) => nums,
+            Backend::Histogram(histogram) => return histogram.median(),
+            Backend::TDigest(tdigest) => return tdigest.percentile(50.0),
+        };
+
+        let len = nums.len();
         if len == 0 {
             return 0.0;
         }
 
-        if len % 2 == 0 {
+        if len.is_multiple_of(2) {
             // Even number of elements - average the middle two
             let mid = len / 2;
-            (self.nums[mid - 1] as f64 + self.nums[mid] as f64) / 2.0
+            (nums[mid - 1] as f64 + nums[mid] as f64) / 2.0
         } else {
             // Odd number of elements - return the middle one
-            self.nums[len / 2] as f64
+            nums[len / 2] as f64
+        }
+    }
+
+    // Value below which `p` percent of the numbers fall, interpolating
+    // between neighbouring ranks; p is clamped to 0..=100 and an empty
+    // finder gives 0.0 like find_median
+    fn percentile(&self, p: f64) -> f64 {
+        let p = p.clamp(0.0, 100.0);
+        match &self.backend {
+            Backend::Exact(Exact { nums, .. }) | Backend::Reservoir(Reservoir { nums, .. }) => {
+                rank_percentile(nums.len(), p, |rank| nums[rank] as f64)
+            }
+            Backend::Histogram(histogram) => rank_percentile(histogram.total, p, |rank| {
+                histogram.nth(rank).unwrap() as f64
+            }),
+            Backend::TDigest(tdigest) => tdigest.percentile(p),
+        }
+    }
+
+    // Adding a value again adds to its weight; zero weights are ignored
+    fn add_weighted(&mut self, num: i32, weight: u64) {
+        if weight == 0 {
+            return;
+        }
+        match self
+            .weighted
+            .binary_search_by_key(&num, |&(value, _)| value)
+        {
+            Ok(pos) => self.weighted[pos].1 += weight as u128,
+            Err(pos) => self.weighted.insert(pos, (num, weight as u128)),
+        }
+        self.total_weight += weight as u128;
+    }
+
+    // The value where the cumulative weight reaches half the total. When it
+    // lands exactly on half, the two values either side are averaged, so
+    // all-equal weights give the same answer as find_median.
+    fn weighted_median(&self) -> f64 {
+        let mut cumulative = 0;
+        for (i, &(value, weight)) in self.weighted.iter().enumerate() {
+            cumulative += weight;
+            // Compared against the remainder rather than doubling
+            let remaining = self.total_weight - cumulative;
+            if cumulative == remaining {
+                let next = self.weighted[i + 1].0;
+                return (value as f64 + next as f64) / 2.0;
+            }
+            if cumulative > remaining {
+                return value as f64;
+            }
+        }
+        0.0
+    }
+
+    fn find_lower_median(&self) -> Option<i32> {
+        // Smaller of the two middle elements for even lengths
+        match &self.backend {
+            Backend::Exact(Exact { nums, .. }) | Backend::Reservoir(Reservoir { nums, .. }) => {
+                nums.get(nums.len().checked_sub(1)? / 2).copied()
+            }
+            Backend::Histogram(histogram) => histogram.nth(histogram.total.checked_sub(1)? / 2),
+            Backend::TDigest(tdigest) => {
+                (tdigest.total > 0).then(|| tdigest.percentile(50.0).floor() as i32)
+            }
+        }
+    }
+
+    fn find_upper_median(&self) -> Option<i32> {
+        // Larger of the two middle elements for even lengths
+        match &self.backend {
+            Backend::Exact(Exact { nums, .. }) | Backend::Reservoir(Reservoir { nums, .. }) => {
+                nums.get(nums.len() / 2).copied()
+            }
+            Backend::Histogram(histogram) => histogram.nth(histogram.total / 2),
+            Backend::TDigest(tdigest) => {
+                (tdigest.total > 0).then(|| tdigest.percentile(50.0).ceil() as i32)
+            }
+        }
+    }
+
+    fn pop_min(&mut self) -> Option<i32> {
+        // Smallest value sits at the front of the sorted vec
+        match &mut self.backend {
+            Backend::Exact(exact) => {
+                if exact.nums.is_empty() {
+                    return None;
+                }
+                let num = exact.nums.remove(0);
+                exact.forget_arrival(num);
+                Some(num)
+            }
+            Backend::Histogram(histogram) => histogram.pop_min(),
+            Backend::Reservoir(reservoir) => {
+                (!reservoir.nums.is_empty()).then(|| reservoir.nums.remove(0))
+            }
+            Backend::TDigest(_) => None,
+        }
+    }
+
+    fn pop_max(&mut self) -> Option<i32> {
+        // Largest value sits at the back
+        match &mut self.backend {
+            Backend::Exact(exact) => {
+                let num = exact.nums.pop()?;
+                exact.forget_arrival(num);
+                Some(num)
+            }
+            Backend::Histogram(histogram) => histogram.pop_max(),
+            Backend::Reservoir(reservoir) => reservoir.nums.pop(),
+            Backend::TDigest(_) => None,
+        }
+    }
+
+    // Median of the `k` most recent numbers still held (all of them if
+    // there are fewer), under the configured policy. None for an empty
+    // window, and always for the bounded modes since they drop arrival order.
+    fn median_of_last_k(&self, k: usize) -> Option<f64> {
+        let Backend::Exact(Exact { arrivals, .. }) = &self.backend else {
+            return None;
+        };
+        let start = arrivals.len().saturating_sub(k);
+        let mut window: Vec<i32> = arrivals.range(start..).copied().collect();
+        if window.is_empty() {
+            return None;
+        }
+        window.sort_unstable();
+
+        let len = window.len();
+        let (lower, upper) = (window[(len - 1) / 2] as f64, window[len / 2] as f64);
+        Some(match self.policy {
+            MedianPolicy::Average | MedianPolicy::Interpolated => (lower + upper) / 2.0,
+            MedianPolicy::Lower => lower,
+            MedianPolicy::Upper => upper,
+        })
+    }
+
+    fn drain(&mut self) -> Vec<i32> {
+        // Hand back everything in sorted order and start over; the mode
+        // (and a reservoir's RNG) is kept. Weighted entries are not handed
+        // back, but they are dropped too.
+        self.weighted.clear();
+        self.total_weight = 0;
+        match &mut self.backend {
+            Backend::Exact(exact) => {
+                exact.arrivals.clear();
+                std::mem::take(&mut exact.nums)
+            }
+            Backend::Histogram(histogram) => histogram.drain(),
+            Backend::Reservoir(reservoir) => {
+                reservoir.seen = 0;
+                std::mem::take(&mut reservoir.nums)
+            }
+            Backend::TDigest(tdigest) => {
+                *tdigest = TDigest::new();
+                Vec::new()
+            }
         }
     }
 }
@@ -42,6 +607,19 @@ fn main() {
     test_odd_number_of_elements();
     test_even_number_of_elements();
     test_stream_of_numbers();
+    test_lower_and_upper_median();
+    test_pop_min_and_max();
+    test_add_num_sorted();
+    test_histogram_matches_exact();
+    test_histogram_clamps_out_of_range();
+    test_reservoir_approximates_median();
+    test_drain();
+    test_add_num_returning_median();
+    test_weighted_median();
+    test_percentile();
+    test_tdigest_approximates_percentiles();
+    test_median_policy();
+    test_median_of_last_k();
 
     println!("All tests passed!");
 }
@@ -150,3 +728,538 @@ fn test_stream_of_numbers() {
 
     println!("✅ Stream of numbers test passed");
 }
+
+fn test_lower_and_upper_median() {
+    let mut finder = MedianFinder::new();
+    assert_eq!(finder.find_lower_median(), None);
+    assert_eq!(finder.find_upper_median(), None);
+
+    finder.add_num(1);
+    finder.add_num(2);
+    finder.add_num(3);
+    finder.add_num(4);
+    assert_eq!(
+        finder.find_lower_median(),
+        Some(2),
+        "Lower median of [1, 2, 3, 4] should be 2"
+    );
+    assert_eq!(
+        finder.find_upper_median(),
+        Some(3),
+        "Upper median of [1, 2, 3, 4] should be 3"
+    );
+    assert_eq!(finder.find_median(), 2.5, "Average median should stay 2.5");
+
+    finder.add_num(5);
+    assert_eq!(
+        finder.find_lower_median(),
+        Some(3),
+        "Odd length has a single middle"
+    );
+    assert_eq!(
+        finder.find_upper_median(),
+        Some(3),
+        "Odd length has a single middle"
+    );
+
+    println!("✅ Lower/upper median test passed");
+}
+
+fn test_pop_min_and_max() {
+    let mut finder = MedianFinder::new();
+    for num in [3, 1, 5, 2, 4] {
+        finder.add_num(num);
+    }
+
+    assert_eq!(finder.pop_min(), Some(1), "pop_min should return 1");
+    assert_eq!(
+        finder.find_median(),
+        3.5,
+        "Median of [2, 3, 4, 5] should be 3.5"
+    );
+
+    assert_eq!(finder.pop_max(), Some(5), "pop_max should return 5");
+    assert_eq!(
+        finder.find_median(),
+        3.0,
+        "Median of [2, 3, 4] should be 3.0"
+    );
+
+    finder.pop_min();
+    finder.pop_max();
+    finder.pop_max();
+    assert_eq!(finder.pop_min(), None, "Empty finder has no min");
+    assert_eq!(finder.pop_max(), None, "Empty finder has no max");
+
+    println!("✅ Pop min/max test passed");
+}
+
+fn test_add_num_sorted() {
+    let mut sorted = MedianFinder::new();
+    let mut generic = MedianFinder::new();
+
+    for num in [-3, 0, 0, 2, 7, 7, 10, 15] {
+        sorted.add_num_sorted(num);
+        generic.add_num(num);
+        assert_eq!(
+            sorted.find_median(),
+            generic.find_median(),
+            "add_num_sorted should match add_num after {}",
+            num
+        );
+    }
+
+    println!("✅ Add num sorted test passed");
+}
+
+fn test_histogram_matches_exact() {
+    let mut histogram = MedianFinder::with_histogram(-10, 100);
+    let mut exact = MedianFinder::new();
+    assert_eq!(
+        histogram.find_median(),
+        0.0,
+        "Empty histogram should return 0.0"
+    );
+
+    for num in [42, -3, 99, 7, 7, 0, 100, -10, 55, 13, 64] {
+        histogram.add_num(num);
+        exact.add_num(num);
+        assert_eq!(
+            histogram.find_median(),
+            exact.find_median(),
+            "Histogram median should match exact median after {}",
+            num
+        );
+        assert_eq!(histogram.find_lower_median(), exact.find_lower_median());
+        assert_eq!(histogram.find_upper_median(), exact.find_upper_median());
+    }
+
+    assert_eq!(histogram.pop_min(), exact.pop_min());
+    assert_eq!(histogram.pop_max(), exact.pop_max());
+    assert_eq!(
+        histogram.find_median(),
+        exact.find_median(),
+        "Histogram median should match after popping both ends"
+    );
+
+    println!("✅ Histogram matches exact test passed");
+}
+
+fn test_histogram_clamps_out_of_range() {
+    let mut finder = MedianFinder::with_histogram(0, 10);
+    finder.add_num(-50);
+    finder.add_num(5);
+    finder.add_num(1_000);
+    assert_eq!(finder.pop_min(), Some(0), "Values below min clamp to min");
+    assert_eq!(finder.pop_max(), Some(10), "Values above max clamp to max");
+    assert_eq!(finder.find_median(), 5.0);
+
+    let mut full_range = MedianFinder::with_histogram(i32::MAX - 1, i32::MAX);
+    full_range.add_num(i32::MAX);
+    assert_eq!(full_range.find_upper_median(), Some(i32::MAX));
+
+    println!("✅ Histogram clamping test passed");
+}
+
+// The numbers a reservoir finder currently holds
+fn sample(finder: &MedianFinder) -> &[i32] {
+    match &finder.backend {
+        Backend::Reservoir(reservoir) => &reservoir.nums,
+        _ => unreachable!("not a reservoir finder"),
+    }
+}
+
+fn test_reservoir_approximates_median() {
+    let mut reservoir = MedianFinder::with_reservoir_seeded(500, 42);
+    let mut exact = MedianFinder::new();
+
+    // Every number in 0..10_000 exactly once, in a scrambled order
+    for i in 0..10_000 {
+        let num = (i * 7_919) % 10_000;
+        reservoir.add_num(num);
+        exact.add_num(num);
+    }
+
+    assert_eq!(
+        sample(&reservoir).len(),
+        500,
+        "Sample should be capped at the size"
+    );
+    assert_eq!(exact.find_median(), 4_999.5);
+    let error = (reservoir.find_median() - exact.find_median()).abs();
+    assert!(
+        error < 500.0,
+        "Sampled median {} should be within 5% of the range of the true median",
+        reservoir.find_median()
+    );
+
+    // The same seed reproduces the same sample
+    let mut again = MedianFinder::with_reservoir_seeded(500, 42);
+    for i in 0..10_000 {
+        again.add_num((i * 7_919) % 10_000);
+    }
+    assert_eq!(
+        sample(&again),
+        sample(&reservoir),
+        "Seeded sampling should be deterministic"
+    );
+
+    let mut unseeded = MedianFinder::with_reservoir(3);
+    for num in 0..100 {
+        unseeded.add_num(num);
+    }
+    assert_eq!(
+        sample(&unseeded).len(),
+        3,
+        "Unseeded reservoir is capped too"
+    );
+
+    let mut empty = MedianFinder::with_reservoir_seeded(0, 1);
+    empty.add_num(5);
+    assert_eq!(
+        empty.find_median(),
+        0.0,
+        "A zero-size reservoir keeps nothing"
+    );
+
+    println!("✅ Reservoir median test passed");
+}
+
+fn test_drain() {
+    let mut finder = MedianFinder::new();
+    for num in [3, 1, 2] {
+        finder.add_num(num);
+    }
+    assert_eq!(
+        finder.drain(),
+        vec![1, 2, 3],
+        "drain should return sorted values"
+    );
+    assert_eq!(finder.find_median(), 0.0, "Drained finder should be empty");
+    assert_eq!(
+        finder.drain(),
+        Vec::<i32>::new(),
+        "Draining twice gives nothing"
+    );
+
+    let mut histogram = MedianFinder::with_histogram(0, 10);
+    for num in [7, 2, 7, 4] {
+        histogram.add_num(num);
+    }
+    assert_eq!(
+        histogram.drain(),
+        vec![2, 4, 7, 7],
+        "Histogram drain expands counts"
+    );
+    assert_eq!(
+        histogram.find_median(),
+        0.0,
+        "Drained histogram should be empty"
+    );
+    histogram.add_num(9);
+    assert_eq!(
+        histogram.find_median(),
+        9.0,
+        "Histogram is reusable after drain"
+    );
+
+    let mut reservoir = MedianFinder::with_reservoir_seeded(2, 7);
+    for num in 0..10 {
+        reservoir.add_num(num);
+    }
+    assert_eq!(
+        reservoir.drain().len(),
+        2,
+        "Reservoir drain returns the sample"
+    );
+    reservoir.add_num(5);
+    reservoir.add_num(6);
+    assert_eq!(
+        reservoir.drain(),
+        vec![5, 6],
+        "A drained reservoir fills up again from scratch"
+    );
+
+    println!("✅ Drain test passed");
+}
+
+fn test_add_num_returning_median() {
+    let mut finder = MedianFinder::new();
+    let medians: Vec<f64> = [41, 35, 62, 4]
+        .into_iter()
+        .map(|num| finder.add_num_returning_median(num))
+        .collect();
+    assert_eq!(
+        medians,
+        vec![41.0, 38.0, 41.0, 38.0],
+        "Running medians should match the stream test"
+    );
+
+    println!("✅ Add num returning median test passed");
+}
+
+fn test_weighted_median() {
+    let mut finder = MedianFinder::new();
+    assert_eq!(
+        finder.weighted_median(),
+        0.0,
+        "Empty weighted median should be 0.0"
+    );
+
+    for (num, weight) in [(3, 10), (1, 1), (2, 1)] {
+        finder.add_weighted(num, weight);
+        finder.add_num(num);
+    }
+    assert_eq!(
+        finder.weighted_median(),
+        3.0,
+        "Most of the weight sits on 3"
+    );
+    assert_eq!(finder.find_median(), 2.0, "Unweighted median is still 2");
+
+    let mut finder = MedianFinder::new();
+    for (num, weight) in [(1, 2), (4, 1), (2, 0), (4, 1)] {
+        finder.add_weighted(num, weight);
+    }
+    assert_eq!(
+        finder.weighted_median(),
+        2.5,
+        "Weight split exactly in half should average both sides"
+    );
+
+    finder.drain();
+    assert_eq!(
+        finder.weighted_median(),
+        0.0,
+        "Drain should drop the weighted entries"
+    );
+    finder.add_weighted(7, 3);
+    assert_eq!(
+        finder.weighted_median(),
+        7.0,
+        "Weights added after a drain should start from zero"
+    );
+
+    let mut finder = MedianFinder::new();
+    finder.add_weighted(1, u64::MAX);
+    finder.add_weighted(2, u64::MAX);
+    assert_eq!(
+        finder.weighted_median(),
+        1.5,
+        "Weights summing past u64::MAX should not overflow"
+    );
+    for _ in 0..3 {
+        finder.add_weighted(3, u64::MAX);
+    }
+    assert_eq!(
+        finder.weighted_median(),
+        3.0,
+        "Repeated huge weights on one value should not overflow"
+    );
+
+    println!("✅ Weighted median test passed");
+}
+
+fn test_percentile() {
+    let mut finder = MedianFinder::new();
+    assert_eq!(
+        finder.percentile(50.0),
+        0.0,
+        "Empty percentile should be 0.0"
+    );
+    for num in [40, 10, 30, 20] {
+        finder.add_num(num);
+    }
+    assert_eq!(
+        finder.percentile(0.0),
+        10.0,
+        "0th percentile is the minimum"
+    );
+    assert_eq!(
+        finder.percentile(100.0),
+        40.0,
+        "100th percentile is the maximum"
+    );
+    assert_eq!(
+        finder.percentile(50.0),
+        finder.find_median(),
+        "50th percentile is the median"
+    );
+    assert_eq!(finder.percentile(90.0), 37.0, "Ranks should interpolate");
+    assert_eq!(finder.percentile(250.0), 40.0, "p is clamped to 100");
+
+    let mut histogram = MedianFinder::with_histogram(0, 100);
+    for num in [40, 10, 30, 20] {
+        histogram.add_num(num);
+    }
+    assert_eq!(
+        histogram.percentile(90.0),
+        37.0,
+        "Histogram percentiles should match exact ones"
+    );
+
+    println!("✅ Percentile test passed");
+}
+
+fn test_tdigest_approximates_percentiles() {
+    // 0..100_000 in a scrambled order (48271 is coprime to 100_000)
+    let stream: Vec<i32> = (0..100_000)
+        .map(|i: i64| (i * 48271 % 100_000) as i32)
+        .collect();
+    let mut exact = MedianFinder::with_histogram(0, 99_999);
+    let mut approx = MedianFinder::with_tdigest();
+    for &num in &stream {
+        exact.add_num(num);
+        approx.add_num(num);
+    }
+
+    // Within 0.5% of the value range
+    let tolerance = 500.0;
+    for p in [50.0, 90.0] {
+        let error = (approx.percentile(p) - exact.percentile(p)).abs();
+        assert!(
+            error <= tolerance,
+            "p{} off by {} (estimate {}, exact {})",
+            p,
+            error,
+            approx.percentile(p),
+            exact.percentile(p)
+        );
+    }
+    assert!(
+        (approx.find_median() - exact.find_median()).abs() <= tolerance,
+        "find_median should use the digest"
+    );
+    let (lower, upper) = (
+        approx.find_lower_median().unwrap() as f64,
+        approx.find_upper_median().unwrap() as f64,
+    );
+    assert!(
+        lower <= approx.find_median() && approx.find_median() <= upper && upper - lower <= 1.0,
+        "Lower and upper medians should bracket the estimate"
+    );
+
+    let Backend::TDigest(tdigest) = &approx.backend else {
+        unreachable!("with_tdigest builds a t-digest finder");
+    };
+    assert!(
+        tdigest.len() <= 2 * TDIGEST_COMPRESSION as usize + TDIGEST_BUFFER,
+        "Digest should stay small, has {} entries",
+        tdigest.len()
+    );
+    assert_eq!(approx.percentile(0.0), 0.0, "Minimum is tracked exactly");
+    assert_eq!(
+        approx.percentile(100.0),
+        99_999.0,
+        "Maximum is tracked exactly"
+    );
+
+    assert_eq!(approx.pop_min(), None, "A digest cannot pop values");
+    assert_eq!(
+        approx.drain(),
+        Vec::<i32>::new(),
+        "A digest has nothing to drain"
+    );
+    assert_eq!(approx.find_median(), 0.0, "Drain resets the digest");
+
+    println!("✅ T-digest test passed");
+}
+
+fn test_median_policy() {
+    let expected = [
+        (MedianPolicy::Average, 2.5),
+        (MedianPolicy::Lower, 2.0),
+        (MedianPolicy::Upper, 3.0),
+        (MedianPolicy::Interpolated, 2.5),
+    ];
+    for (policy, median) in expected {
+        let mut finder = MedianFinder::with_policy(policy);
+        assert_eq!(
+            finder.find_median(),
+            0.0,
+            "Empty median is 0.0 for {:?}",
+            policy
+        );
+        for num in [4, 1, 3, 2] {
+            finder.add_num(num);
+        }
+        assert_eq!(
+            finder.find_median(),
+            median,
+            "Wrong median of [1, 2, 3, 4] for {:?}",
+            policy
+        );
+        finder.add_num(5);
+        assert_eq!(
+            finder.find_median(),
+            3.0,
+            "Odd counts have one median for {:?}",
+            policy
+        );
+    }
+
+    println!("✅ Median policy test passed");
+}
+
+fn test_median_of_last_k() {
+    let mut finder = MedianFinder::new();
+    assert_eq!(
+        finder.median_of_last_k(3),
+        None,
+        "Empty window has no median"
+    );
+    for num in [1, 2, 3, 4, 5] {
+        finder.add_num(num);
+    }
+    assert_eq!(finder.median_of_last_k(3), Some(4.0), "Median of [3, 4, 5]");
+    assert_eq!(finder.median_of_last_k(2), Some(4.5), "Median of [4, 5]");
+    assert_eq!(
+        finder.median_of_last_k(10),
+        Some(3.0),
+        "k past the size uses everything"
+    );
+    assert_eq!(finder.find_median(), 3.0, "The full data is untouched");
+
+    // Out-of-order arrivals are windowed by time, not by value
+    finder.add_num(0);
+    assert_eq!(finder.median_of_last_k(3), Some(4.0), "Median of [4, 5, 0]");
+
+    finder.pop_max();
+    assert_eq!(
+        finder.median_of_last_k(3),
+        Some(3.0),
+        "Popped 5 leaves [3, 4, 0]"
+    );
+
+    let mut lower = MedianFinder::with_policy(MedianPolicy::Lower);
+    for num in [1, 2, 3, 4, 5] {
+        lower.add_num(num);
+    }
+    assert_eq!(
+        lower.median_of_last_k(2),
+        Some(4.0),
+        "Windowed median follows the policy"
+    );
+
+    finder.drain();
+    assert_eq!(finder.median_of_last_k(3), None, "Drain empties the window");
+
+    // The bounded modes drop arrival order, so there is no window
+    let mut bounded = [
+        MedianFinder::with_histogram(0, 10),
+        MedianFinder::with_reservoir(10),
+        MedianFinder::with_tdigest(),
+    ];
+    for finder in &mut bounded {
+        for num in [1, 2, 3] {
+            finder.add_num(num);
+        }
+        assert_eq!(
+            finder.median_of_last_k(2),
+            None,
+            "Only the exact mode keeps arrival order"
+        );
+    }
+
+    println!("✅ Median of last k test passed");
+}
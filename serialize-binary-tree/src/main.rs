@@ -1,5 +1,7 @@
 use std::cell::RefCell;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+use std::io::{self, BufRead, Write};
 use std::rc::Rc;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -7,6 +9,9 @@ pub struct TreeNode {
     pub val: i32,
     pub left: Option<Rc<RefCell<TreeNode>>>,
     pub right: Option<Rc<RefCell<TreeNode>>>,
+    // Extra per-node bit (e.g. a red-black color); only the *_with_flags
+    // codec methods persist it
+    pub flag: bool,
 }
 
 impl TreeNode {
@@ -16,85 +21,535 @@ impl TreeNode {
             val,
             left: None,
             right: None,
+            flag: false,
         }))
     }
 }
 
-struct Codec {}
+#[derive(Debug, PartialEq, Eq)]
+enum CodecError {
+    // The node with this value is reachable through more than one parent
+    // (or from itself), so the input is a DAG or a cycle rather than a tree
+    SharedNode(i32),
+    // The tree has more nodes than the caller allowed
+    #[allow(dead_code)]
+    TooLarge(usize),
+    // The node with this value breaks left < node < right
+    #[allow(dead_code)]
+    NotBst(i32),
+    // A token that is neither the null marker nor a node
+    BadToken(String),
+}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CodecError::SharedNode(val) => {
+                write!(f, "node {} is shared by more than one parent", val)
+            }
+            CodecError::TooLarge(max_nodes) => {
+                write!(f, "tree has more than {} nodes", max_nodes)
+            }
+            CodecError::NotBst(val) => {
+                write!(f, "node {} is out of order for a binary search tree", val)
+            }
+            CodecError::BadToken(token) => write!(f, "invalid token {:?}", token),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+struct Codec {
+    // Written in place of a missing child
+    null_marker: String,
+    // Written after every token
+    delim: char,
+}
 
 impl Codec {
     fn new() -> Self {
-        Codec {}
+        Self::with_markers("n", '/')
+    }
+
+    // Panics if the markers would make the output ambiguous: the null
+    // marker must be non-empty, free of the delimiter and of surrounding
+    // whitespace, and not an i32. The delimiter must be ASCII so
+    // deserialize_from can split the raw bytes on it, and cannot be
+    // whitespace (split trims it away, and forests are one tree per line)
+    // or ':' (which separates value and flag in the flags format).
+    fn with_markers(null_marker: &str, delim: char) -> Self {
+        assert!(
+            !null_marker.is_empty()
+                && !null_marker.contains(delim)
+                && null_marker.trim() == null_marker
+                && null_marker.parse::<i32>().is_err(),
+            "invalid null marker {:?}",
+            null_marker
+        );
+        assert!(delim.is_ascii(), "delimiter {:?} is not ASCII", delim);
+        assert!(
+            !delim.is_whitespace() && delim != ':',
+            "delimiter {:?} clashes with the codec's other formats",
+            delim
+        );
+        Codec {
+            null_marker: null_marker.to_string(),
+            delim,
+        }
     }
 
+    // Panics if a node is shared between parents; use try_serialize to
+    // handle that case
     fn serialize(&self, root: Option<Rc<RefCell<TreeNode>>>) -> String {
-        if root.is_none() {
-            return "n".to_string();
+        self.try_serialize(root)
+            .unwrap_or_else(|e| panic!("cannot serialize: {}", e))
+    }
+
+    fn try_serialize(&self, root: Option<Rc<RefCell<TreeNode>>>) -> Result<String, CodecError> {
+        check_tree(&root)?;
+        let mut buf = Vec::new();
+        // Writing into a Vec<u8> cannot fail
+        write_level_order(root, &mut buf, &self.null_marker, self.delim).unwrap();
+        Ok(String::from_utf8(buf).unwrap())
+    }
+
+    // Same output as serialize, but gives up as soon as more than
+    // `max_nodes` nodes have been emitted instead of walking the whole tree
+    #[allow(dead_code)]
+    fn serialize_bounded(
+        &self,
+        root: Option<Rc<RefCell<TreeNode>>>,
+        max_nodes: usize,
+    ) -> Result<String, CodecError> {
+        // Shared nodes are caught along the way, so the seen set stays
+        // within the bound too. The first problem aborts the walk and is
+        // kept here, since the writer can only report an io::Error.
+        let mut seen = HashSet::new();
+        let mut error = None;
+        let mut buf = Vec::new();
+        let written = write_level_order_by(
+            root,
+            &mut buf,
+            &self.null_marker,
+            self.delim,
+            |writer, node| {
+                if !seen.insert(node as *const TreeNode) {
+                    error = Some(CodecError::SharedNode(node.val));
+                } else if seen.len() > max_nodes {
+                    error = Some(CodecError::TooLarge(max_nodes));
+                }
+                if error.is_some() {
+                    return Err(io::ErrorKind::Other.into());
+                }
+                write!(writer, "{}", node.val)
+            },
+        );
+        if let Some(e) = error {
+            return Err(e);
         }
+        // Writing into a Vec<u8> cannot fail otherwise
+        written.unwrap();
+        Ok(String::from_utf8(buf).unwrap())
+    }
+
+    #[allow(dead_code)]
+    fn serialize_to<W: Write>(
+        &self,
+        root: Option<Rc<RefCell<TreeNode>>>,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        check_tree(&root).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        write_level_order(root, writer, &self.null_marker, self.delim)
+    }
+
+    fn deserialize(&self, data: String) -> Option<Rc<RefCell<TreeNode>>> {
+        self.deserialize_with_delim(data, self.delim)
+    }
+
+    // For hand-edited input: tokens may be separated by something other
+    // than the configured one and padded with whitespace. Panics on a
+    // token that is neither a number nor the null marker.
+    fn deserialize_with_delim(&self, data: String, delim: char) -> Option<Rc<RefCell<TreeNode>>> {
+        self.parse_tree(&data, delim, parse_node)
+            .unwrap_or_else(|e| panic!("cannot deserialize: {}", e))
+    }
 
-        let mut result = String::new();
+    // Level-order tokens split on `delim`, with `node` parsing every
+    // non-null token; the first token it rejects is the error
+    fn parse_tree(
+        &self,
+        data: &str,
+        delim: char,
+        node: impl Fn(&str) -> Result<Rc<RefCell<TreeNode>>, CodecError>,
+    ) -> Result<Option<Rc<RefCell<TreeNode>>>, CodecError> {
+        let nodes = split(data, delim);
+        if nodes.is_empty() || nodes[0] == self.null_marker {
+            return Ok(None);
+        }
+
+        build_level_order(
+            &nodes,
+            |token| *token == self.null_marker,
+            |token| node(token),
+        )
+        .map(Some)
+    }
+
+    // Like serialize, but every node is written as `value:flag` with the
+    // flag as 1 or 0, e.g. "1:1/2:0/n/n/n/"
+    #[allow(dead_code)]
+    fn serialize_with_flags(&self, root: Option<Rc<RefCell<TreeNode>>>) -> String {
+        if let Err(e) = check_tree(&root) {
+            panic!("cannot serialize: {}", e);
+        }
+        let mut buf = Vec::new();
+        // Writing into a Vec<u8> cannot fail
+        write_level_order_by(
+            root,
+            &mut buf,
+            &self.null_marker,
+            self.delim,
+            |writer, node| write!(writer, "{}:{}", node.val, node.flag as u8),
+        )
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    // Every node token must be `value:flag` with the flag as 1 or 0
+    #[allow(dead_code)]
+    fn deserialize_with_flags(
+        &self,
+        data: String,
+    ) -> Result<Option<Rc<RefCell<TreeNode>>>, CodecError> {
+        self.parse_tree(&data, self.delim, |token| {
+            let bad_token = || CodecError::BadToken(token.to_string());
+            let (val, flag) = token.split_once(':').ok_or_else(bad_token)?;
+            let node = parse_node(val.trim()).map_err(|_| bad_token())?;
+            node.borrow_mut().flag = match flag.trim() {
+                "1" => true,
+                "0" => false,
+                _ => return Err(bad_token()),
+            };
+            Ok(node)
+        })
+    }
+
+    // LeetCode's array form: level order with None for a missing child.
+    // Same rules as deserialize, so trailing Nones may be left off. Takes
+    // `&self` to pair with to_level_order like the other codec methods.
+    #[allow(dead_code)]
+    #[allow(clippy::wrong_self_convention)]
+    fn from_level_order(&self, values: &[Option<i32>]) -> Option<Rc<RefCell<TreeNode>>> {
+        values.first()?.as_ref()?;
+        let Ok(root) = build_level_order(values, Option::is_none, |val| {
+            Ok::<_, Infallible>(TreeNode::new(val.unwrap()))
+        });
+        Some(root)
+    }
+
+    // Inverse of from_level_order, with trailing Nones dropped the way
+    // LeetCode prints trees. Panics on shared nodes, like serialize.
+    #[allow(dead_code)]
+    fn to_level_order(&self, root: Option<Rc<RefCell<TreeNode>>>) -> Vec<Option<i32>> {
+        if let Err(e) = check_tree(&root) {
+            panic!("cannot serialize: {}", e);
+        }
+        let mut values = Vec::new();
         let mut queue = VecDeque::new();
         queue.push_back(root);
-
         while let Some(node_opt) = queue.pop_front() {
             match node_opt {
                 Some(node) => {
                     let node_ref = node.borrow();
-                    result.push_str(&node_ref.val.to_string());
-                    result.push('/');
-
+                    values.push(Some(node_ref.val));
                     queue.push_back(node_ref.left.clone());
                     queue.push_back(node_ref.right.clone());
                 }
-                None => {
-                    result.push_str("n/");
-                }
+                None => values.push(None),
             }
         }
+        while values.last() == Some(&None) {
+            values.pop();
+        }
+        values
+    }
 
-        result
+    // Like deserialize, but the result must be a binary search tree with
+    // strictly increasing values in order, and a bad token is an error
+    // rather than a panic
+    #[allow(dead_code)]
+    fn deserialize_bst(&self, data: String) -> Result<Option<Rc<RefCell<TreeNode>>>, CodecError> {
+        let root = self.parse_tree(&data, self.delim, parse_node)?;
+        check_bst(&root, i64::MIN, i64::MAX)?;
+        Ok(root)
     }
 
-    fn deserialize(&self, data: String) -> Option<Rc<RefCell<TreeNode>>> {
-        if data == "n" {
-            return None;
-        }
+    // One serialized tree per line; an empty slice gives an empty string
+    #[allow(dead_code)]
+    fn serialize_forest(&self, roots: &[Option<Rc<RefCell<TreeNode>>>]) -> String {
+        roots
+            .iter()
+            .map(|root| self.serialize(root.clone()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-        let nodes = split(&data);
-        if nodes.is_empty() {
-            return None;
-        }
+    #[allow(dead_code)]
+    fn deserialize_forest(&self, data: String) -> Vec<Option<Rc<RefCell<TreeNode>>>> {
+        data.lines()
+            .map(|line| self.deserialize(line.to_string()))
+            .collect()
+    }
+
+    // Multi-line sketch for debugging, root first and each child indented
+    // under its parent, left before right:
+    //
+    //   1
+    //   |-- 2
+    //   `-- 3
+    //       |-- 4
+    //       `-- 5
+    //
+    // A missing child is drawn as "-" when its sibling exists. A node
+    // reached a second time is marked "(shared)" and not expanded again.
+    #[allow(dead_code)]
+    fn pretty_print(root: &Option<Rc<RefCell<TreeNode>>>) -> String {
+        let Some(root) = root else {
+            return "(empty)\n".to_string();
+        };
+        let mut out = String::new();
+        let mut seen = HashSet::new();
+        write_pretty(root, "", "", &mut seen, &mut out);
+        out
+    }
 
-        let root = TreeNode::new(nodes[0].parse().unwrap());
+    #[allow(dead_code)]
+    fn deserialize_from<R: BufRead>(&self, reader: R) -> io::Result<Option<Rc<RefCell<TreeNode>>>> {
+        // Pull one token at a time instead of reading the whole input
+        let mut tokens = reader
+            .split(self.delim as u8)
+            .filter_map(|token| match token {
+                Ok(bytes) => {
+                    let trimmed = bytes.trim_ascii();
+                    (!trimmed.is_empty()).then(|| parse_token(trimmed, &self.null_marker))
+                }
+                Err(e) => Some(Err(e)),
+            });
+
+        let root = match tokens.next().transpose()?.flatten() {
+            Some(val) => TreeNode::new(val),
+            None => return Ok(None),
+        };
         let mut queue = VecDeque::new();
         queue.push_back(root.clone());
 
-        let mut i = 1;
-        while !queue.is_empty() && i < nodes.len() {
-            let current = queue.pop_front().unwrap();
+        while let Some(current) = queue.pop_front() {
             let mut current_mut = current.borrow_mut();
 
-            if nodes[i] != "n" {
-                let left = TreeNode::new(nodes[i].parse().unwrap());
+            let Some(left) = tokens.next().transpose()? else {
+                break;
+            };
+            if let Some(val) = left {
+                let left = TreeNode::new(val);
                 current_mut.left = Some(left.clone());
                 queue.push_back(left);
             }
-            i += 1;
 
-            if i < nodes.len() && nodes[i] != "n" {
-                let right = TreeNode::new(nodes[i].parse().unwrap());
+            let Some(right) = tokens.next().transpose()? else {
+                break;
+            };
+            if let Some(val) = right {
+                let right = TreeNode::new(val);
                 current_mut.right = Some(right.clone());
                 queue.push_back(right);
             }
-            i += 1;
         }
 
-        Some(root)
+        Ok(Some(root))
     }
 }
 
-fn split(data: &str) -> Vec<&str> {
-    data.split('/').filter(|x| !x.is_empty()).collect()
+// `lead` goes in front of this node's value, `indent` in front of
+// everything below it
+#[allow(dead_code)]
+fn write_pretty(
+    node: &Rc<RefCell<TreeNode>>,
+    lead: &str,
+    indent: &str,
+    seen: &mut HashSet<*const RefCell<TreeNode>>,
+    out: &mut String,
+) {
+    let node_ref = node.borrow();
+    if !seen.insert(Rc::as_ptr(node)) {
+        out.push_str(&format!("{}{} (shared)\n", lead, node_ref.val));
+        return;
+    }
+    out.push_str(&format!("{}{}\n", lead, node_ref.val));
+
+    let children = match (&node_ref.left, &node_ref.right) {
+        (None, None) => return,
+        (left, right) => [left, right],
+    };
+    for (i, child) in children.into_iter().enumerate() {
+        let (branch, next_indent) = if i == 0 {
+            ("|-- ", format!("{}|   ", indent))
+        } else {
+            ("`-- ", format!("{}    ", indent))
+        };
+        let lead = format!("{}{}", indent, branch);
+        match child {
+            Some(child) => write_pretty(child, &lead, &next_indent, seen, out),
+            None => out.push_str(&format!("{}-\n", lead)),
+        }
+    }
+}
+
+fn split(data: &str, delim: char) -> Vec<&str> {
+    data.split(delim)
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+// Walk every node reachable from `root`, failing on the first one seen twice
+fn check_tree(root: &Option<Rc<RefCell<TreeNode>>>) -> Result<(), CodecError> {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<Rc<RefCell<TreeNode>>> = root.iter().cloned().collect();
+    while let Some(node) = stack.pop() {
+        if !seen.insert(Rc::as_ptr(&node)) {
+            return Err(CodecError::SharedNode(node.borrow().val));
+        }
+        let node_ref = node.borrow();
+        stack.extend(node_ref.left.clone());
+        stack.extend(node_ref.right.clone());
+    }
+    Ok(())
+}
+
+// Every value in this subtree must lie strictly between `low` and `high`
+#[allow(dead_code)]
+fn check_bst(node: &Option<Rc<RefCell<TreeNode>>>, low: i64, high: i64) -> Result<(), CodecError> {
+    let Some(node) = node else {
+        return Ok(());
+    };
+    let node_ref = node.borrow();
+    let val = node_ref.val as i64;
+    if val <= low || val >= high {
+        return Err(CodecError::NotBst(node_ref.val));
+    }
+    check_bst(&node_ref.left, low, val)?;
+    check_bst(&node_ref.right, val, high)
+}
+
+// Rebuild a tree from level-order entries whose first one is not null;
+// `make` turns every entry that `is_null` rejects into a node, and the
+// first error it returns stops the build
+fn build_level_order<T, E>(
+    nodes: &[T],
+    is_null: impl Fn(&T) -> bool,
+    make: impl Fn(&T) -> Result<Rc<RefCell<TreeNode>>, E>,
+) -> Result<Rc<RefCell<TreeNode>>, E> {
+    let root = make(&nodes[0])?;
+    let mut queue = VecDeque::new();
+    queue.push_back(root.clone());
+
+    let mut i = 1;
+    while !queue.is_empty() && i < nodes.len() {
+        let current = queue.pop_front().unwrap();
+        let mut current_mut = current.borrow_mut();
+
+        if !is_null(&nodes[i]) {
+            let left = make(&nodes[i])?;
+            current_mut.left = Some(left.clone());
+            queue.push_back(left);
+        }
+        i += 1;
+
+        if i < nodes.len() && !is_null(&nodes[i]) {
+            let right = make(&nodes[i])?;
+            current_mut.right = Some(right.clone());
+            queue.push_back(right);
+        }
+        i += 1;
+    }
+
+    Ok(root)
+}
+
+// BFS over the tree, writing each value followed by `delim` and `null`
+// then `delim` for a missing child; an empty tree is just `null`
+fn write_level_order<W: Write>(
+    root: Option<Rc<RefCell<TreeNode>>>,
+    writer: &mut W,
+    null: &str,
+    delim: char,
+) -> io::Result<()> {
+    write_level_order_by(root, writer, null, delim, |writer, node| {
+        write!(writer, "{}", node.val)
+    })
+}
+
+// Same walk as write_level_order, with `token` writing each node
+fn write_level_order_by<W: Write>(
+    root: Option<Rc<RefCell<TreeNode>>>,
+    writer: &mut W,
+    null: &str,
+    delim: char,
+    mut token: impl FnMut(&mut W, &TreeNode) -> io::Result<()>,
+) -> io::Result<()> {
+    if root.is_none() {
+        return writer.write_all(null.as_bytes());
+    }
+    let mut delim_buf = [0; 4];
+    let delim = delim.encode_utf8(&mut delim_buf).as_bytes();
+
+    let mut queue = VecDeque::new();
+    queue.push_back(root);
+
+    while let Some(node_opt) = queue.pop_front() {
+        match node_opt {
+            Some(node) => {
+                let node_ref = node.borrow();
+                token(writer, &node_ref)?;
+                writer.write_all(delim)?;
+
+                queue.push_back(node_ref.left.clone());
+                queue.push_back(node_ref.right.clone());
+            }
+            None => {
+                writer.write_all(null.as_bytes())?;
+                writer.write_all(delim)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// `null` is a null child, anything else must be an i32
+fn parse_node(token: &str) -> Result<Rc<RefCell<TreeNode>>, CodecError> {
+    token
+        .parse()
+        .map(TreeNode::new)
+        .map_err(|_| CodecError::BadToken(token.to_string()))
+}
+
+#[allow(dead_code)]
+fn parse_token(token: &[u8], null: &str) -> io::Result<Option<i32>> {
+    if token == null.as_bytes() {
+        return Ok(None);
+    }
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid token {:?}", String::from_utf8_lossy(token)),
+            )
+        })
 }
 
 fn main() {
@@ -121,12 +576,28 @@ fn main() {
     let serialized = codec.serialize(Some(node1.clone()));
     println!("{:?}", serialized);
     let deserialized = codec.deserialize(serialized);
+    println!("{:?}", deserialized);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    //     1
+    //    / \
+    //   2   3
+    //      / \
+    //     4   5
+    fn sample_tree() -> Rc<RefCell<TreeNode>> {
+        let node1 = TreeNode::new(1);
+        let node3 = TreeNode::new(3);
+        node3.borrow_mut().left = Some(TreeNode::new(4));
+        node3.borrow_mut().right = Some(TreeNode::new(5));
+        node1.borrow_mut().left = Some(TreeNode::new(2));
+        node1.borrow_mut().right = Some(node3);
+        node1
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let codec = Codec::new();
@@ -154,4 +625,379 @@ mod tests {
 
         assert_eq!(codec.serialize(Some(node1)), codec.serialize(deserialized));
     }
+
+    #[test]
+    fn test_serialize_to_matches_serialize() {
+        let codec = Codec::new();
+        let root = sample_tree();
+
+        let mut buf: Vec<u8> = Vec::new();
+        codec.serialize_to(Some(root.clone()), &mut buf).unwrap();
+        assert_eq!(buf, codec.serialize(Some(root)).into_bytes());
+
+        let mut empty: Vec<u8> = Vec::new();
+        codec.serialize_to(None, &mut empty).unwrap();
+        assert_eq!(empty, b"n");
+    }
+
+    #[test]
+    fn test_deserialize_from_reader() {
+        let codec = Codec::new();
+        let root = sample_tree();
+        let serialized = codec.serialize(Some(root.clone()));
+
+        let reader = io::BufReader::new(serialized.as_bytes());
+        let deserialized = codec.deserialize_from(reader).unwrap();
+        assert_eq!(deserialized, Some(root));
+
+        let empty = codec.deserialize_from(io::BufReader::new(&b"n"[..]));
+        assert_eq!(empty.unwrap(), None);
+
+        let bad = codec.deserialize_from(io::BufReader::new(&b"1/x/n/"[..]));
+        assert_eq!(bad.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_shared_node_is_reported() {
+        let codec = Codec::new();
+        let root = TreeNode::new(1);
+        let shared = TreeNode::new(2);
+        root.borrow_mut().left = Some(shared.clone());
+        root.borrow_mut().right = Some(shared);
+
+        assert_eq!(
+            codec.try_serialize(Some(root.clone())),
+            Err(CodecError::SharedNode(2))
+        );
+        let mut buf: Vec<u8> = Vec::new();
+        let err = codec.serialize_to(Some(root), &mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let codec = Codec::new();
+        let root = TreeNode::new(1);
+        let child = TreeNode::new(2);
+        root.borrow_mut().right = Some(child.clone());
+        child.borrow_mut().left = Some(root.clone());
+
+        assert_eq!(
+            codec.try_serialize(Some(root.clone())),
+            Err(CodecError::SharedNode(1))
+        );
+        // Break the cycle so the nodes are freed
+        child.borrow_mut().left = None;
+    }
+
+    #[test]
+    #[should_panic(expected = "node 2 is shared by more than one parent")]
+    fn test_serialize_panics_on_shared_node() {
+        let root = TreeNode::new(1);
+        let shared = TreeNode::new(2);
+        root.borrow_mut().left = Some(shared.clone());
+        root.borrow_mut().right = Some(shared);
+        Codec::new().serialize(Some(root));
+    }
+
+    #[test]
+    fn test_forest_round_trip() {
+        let codec = Codec::new();
+        let single = TreeNode::new(7);
+        let left_only = TreeNode::new(8);
+        left_only.borrow_mut().left = Some(TreeNode::new(9));
+        let roots = vec![Some(sample_tree()), None, Some(single), Some(left_only)];
+
+        let serialized = codec.serialize_forest(&roots);
+        assert_eq!(serialized.lines().count(), roots.len());
+
+        let deserialized = codec.deserialize_forest(serialized);
+        assert_eq!(deserialized.len(), roots.len());
+        assert_eq!(deserialized, roots);
+
+        assert_eq!(codec.serialize_forest(&[]), "");
+        assert!(codec.deserialize_forest(String::new()).is_empty());
+    }
+
+    #[test]
+    fn test_serialize_bounded_under_limit() {
+        let codec = Codec::new();
+        let root = sample_tree();
+
+        assert_eq!(
+            codec.serialize_bounded(Some(root.clone()), 5),
+            Ok(codec.serialize(Some(root)))
+        );
+        assert_eq!(codec.serialize_bounded(None, 0), Ok("n".to_string()));
+    }
+
+    #[test]
+    fn test_serialize_bounded_over_limit() {
+        let codec = Codec::new();
+
+        assert_eq!(
+            codec.serialize_bounded(Some(sample_tree()), 4),
+            Err(CodecError::TooLarge(4))
+        );
+        assert_eq!(
+            codec.serialize_bounded(Some(TreeNode::new(1)), 0),
+            Err(CodecError::TooLarge(0))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bst_accepts_valid_tree() {
+        let codec = Codec::new();
+        //     4
+        //    / \
+        //   2   6
+        //  / \   \
+        // 1   3   7
+        let tree = codec.deserialize_bst("4/2/6/1/3/n/7/n/n/n/n/n/n/".to_string());
+        let tree = tree.unwrap().unwrap();
+        assert_eq!(codec.serialize(Some(tree)), "4/2/6/1/3/n/7/n/n/n/n/n/n/");
+        assert_eq!(codec.deserialize_bst("n".to_string()), Ok(None));
+    }
+
+    #[test]
+    fn test_deserialize_bst_rejects_out_of_order_node() {
+        let codec = Codec::new();
+        // 5 sits in the left subtree of 4, which is only locally ordered
+        assert_eq!(
+            codec.deserialize_bst("4/2/6/1/5/n/n/n/n/n/n/".to_string()),
+            Err(CodecError::NotBst(5))
+        );
+        // Duplicates are not allowed either
+        assert_eq!(
+            codec.deserialize_bst("2/2/n/n/n/".to_string()),
+            Err(CodecError::NotBst(2))
+        );
+        assert_eq!(
+            codec.deserialize_bst(codec.serialize(Some(sample_tree()))),
+            Err(CodecError::NotBst(2))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bst_reports_bad_token() {
+        let codec = Codec::new();
+        assert_eq!(
+            codec.deserialize_bst("4/2/oops/n/n/n/n/".to_string()),
+            Err(CodecError::BadToken("oops".to_string()))
+        );
+        assert_eq!(
+            codec.deserialize_bst("99999999999/n/n/".to_string()),
+            Err(CodecError::BadToken("99999999999".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid token \"oops\"")]
+    fn test_deserialize_panics_on_bad_token() {
+        Codec::new().deserialize("1/oops/n/".to_string());
+    }
+
+    #[test]
+    fn test_deserialize_tolerates_whitespace() {
+        let codec = Codec::new();
+        let expected = codec.deserialize("1/2/3/".to_string());
+
+        let spaced = codec.deserialize(" 1 / 2 / 3 ".to_string());
+        assert_eq!(spaced, expected);
+        assert_eq!(codec.serialize(spaced), "1/2/3/n/n/n/n/");
+        assert_eq!(codec.deserialize(" n ".to_string()), None);
+    }
+
+    #[test]
+    fn test_deserialize_with_delim() {
+        let codec = Codec::new();
+        let expected = codec.deserialize("1/2/3/n/n/4/n/".to_string());
+
+        let comma = codec.deserialize_with_delim("1,2,3,n,n,4,n,".to_string(), ',');
+        assert_eq!(comma, expected);
+        let padded = codec.deserialize_with_delim("1, 2, 3, n, n, 4".to_string(), ',');
+        assert_eq!(padded, expected);
+        assert_eq!(codec.deserialize_with_delim("n".to_string(), ','), None);
+        assert_eq!(codec.deserialize_with_delim(String::new(), ','), None);
+    }
+
+    #[test]
+    fn test_flags_round_trip() {
+        let codec = Codec::new();
+        let root = sample_tree();
+        root.borrow_mut().flag = true;
+        let right = root.borrow().right.clone().unwrap();
+        right.borrow_mut().flag = true;
+        right.borrow().right.as_ref().unwrap().borrow_mut().flag = true;
+
+        let serialized = codec.serialize_with_flags(Some(root.clone()));
+        assert_eq!(serialized, "1:1/2:0/3:1/n/n/4:0/5:1/n/n/n/n/");
+        assert_eq!(
+            codec.deserialize_with_flags(serialized),
+            Ok(Some(root.clone()))
+        );
+        assert_eq!(codec.deserialize_with_flags("n".to_string()), Ok(None));
+        // A missing flag, a flag other than 0 or 1, and a bad value
+        for (data, token) in [("1:0/2/n/", "2"), ("1:2/n/n/", "1:2"), ("x:1/n/n/", "x:1")] {
+            assert_eq!(
+                codec.deserialize_with_flags(data.to_string()),
+                Err(CodecError::BadToken(token.to_string()))
+            );
+        }
+
+        // The default codec ignores the flag
+        let plain = codec.deserialize(codec.serialize(Some(root.clone())));
+        assert_eq!(codec.serialize(plain.clone()), codec.serialize(Some(root)));
+        assert!(!plain.unwrap().borrow().flag);
+    }
+
+    #[test]
+    fn test_custom_markers_round_trip() {
+        let codec = Codec::with_markers("NULL", ';');
+        let serialized = codec.serialize(Some(sample_tree()));
+        assert_eq!(serialized, "1;2;3;NULL;NULL;4;5;NULL;NULL;NULL;NULL;");
+
+        let deserialized = codec.deserialize(serialized.clone());
+        assert_eq!(codec.serialize(deserialized), serialized);
+        assert_eq!(
+            Codec::new().serialize(codec.deserialize(serialized.clone())),
+            Codec::new().serialize(Some(sample_tree()))
+        );
+
+        let mut streamed = Vec::new();
+        codec
+            .serialize_to(Some(sample_tree()), &mut streamed)
+            .unwrap();
+        assert_eq!(streamed, serialized.as_bytes());
+        let from_reader = codec
+            .deserialize_from(io::BufReader::new(serialized.as_bytes()))
+            .unwrap();
+        assert_eq!(codec.serialize(from_reader), serialized);
+
+        assert_eq!(codec.serialize(None), "NULL");
+        assert_eq!(codec.deserialize("NULL".to_string()), None);
+        // The default marker is just another value now
+        assert!(codec
+            .deserialize_from(io::BufReader::new(&b"1;n;"[..]))
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid null marker")]
+    fn test_null_marker_containing_delim_panics() {
+        Codec::with_markers("a;b", ';');
+    }
+
+    #[test]
+    #[should_panic(expected = "clashes")]
+    fn test_newline_delim_panics() {
+        Codec::with_markers("n", '\n');
+    }
+
+    #[test]
+    #[should_panic(expected = "clashes")]
+    fn test_colon_delim_panics() {
+        Codec::with_markers("n", ':');
+    }
+
+    #[test]
+    #[should_panic(expected = "clashes")]
+    fn test_whitespace_delim_panics() {
+        Codec::with_markers("n", ' ');
+    }
+
+    #[test]
+    #[should_panic(expected = "clashes")]
+    fn test_tab_delim_panics() {
+        Codec::with_markers("n", '\t');
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let rendered = Codec::pretty_print(&Some(sample_tree()));
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "1");
+        assert!(lines[1].ends_with(" 2"));
+        assert!(lines[2].ends_with(" 3"));
+        // 4 and 5 hang one level deeper than 2 and 3
+        let depth =
+            |line: &str| line.len() - line.trim_start_matches(|c: char| !c.is_ascii_digit()).len();
+        assert_eq!(depth(lines[1]), depth(lines[2]));
+        assert!(lines[3].ends_with(" 4") && depth(lines[3]) > depth(lines[2]));
+        assert!(lines[4].ends_with(" 5") && depth(lines[4]) == depth(lines[3]));
+
+        // A lone right child still shows where the left one would be
+        let root = TreeNode::new(7);
+        root.borrow_mut().right = Some(TreeNode::new(8));
+        assert_eq!(Codec::pretty_print(&Some(root)), "7\n|-- -\n`-- 8\n");
+
+        let shared = TreeNode::new(2);
+        let root = TreeNode::new(1);
+        root.borrow_mut().left = Some(shared.clone());
+        root.borrow_mut().right = Some(shared);
+        assert!(Codec::pretty_print(&Some(root)).contains("2 (shared)"));
+
+        assert_eq!(Codec::pretty_print(&None), "(empty)\n");
+    }
+
+    #[test]
+    fn test_asymmetric_children_round_trip() {
+        //       1
+        //      / \
+        //     2   3
+        //      \  /
+        //      4 5
+        //     /   \
+        //    6     7
+        let root = TreeNode::new(1);
+        let node2 = TreeNode::new(2);
+        let node3 = TreeNode::new(3);
+        let node4 = TreeNode::new(4);
+        let node5 = TreeNode::new(5);
+        node4.borrow_mut().left = Some(TreeNode::new(6));
+        node5.borrow_mut().right = Some(TreeNode::new(7));
+        node2.borrow_mut().right = Some(node4);
+        node3.borrow_mut().left = Some(node5);
+        root.borrow_mut().left = Some(node2);
+        root.borrow_mut().right = Some(node3);
+
+        let codec = Codec::new();
+        let serialized = codec.serialize(Some(root.clone()));
+        assert_eq!(serialized, "1/2/3/n/4/5/n/6/n/n/7/n/n/n/n/");
+
+        let deserialized = codec.deserialize(serialized).unwrap();
+        assert_eq!(deserialized, root);
+        let node2 = deserialized.borrow().left.clone().unwrap();
+        assert!(node2.borrow().left.is_none());
+        assert_eq!(node2.borrow().right.as_ref().unwrap().borrow().val, 4);
+        let node3 = deserialized.borrow().right.clone().unwrap();
+        assert_eq!(node3.borrow().left.as_ref().unwrap().borrow().val, 5);
+        assert!(node3.borrow().right.is_none());
+
+        let from_reader = codec
+            .deserialize_from(io::BufReader::new(
+                codec.serialize(Some(root.clone())).as_bytes(),
+            ))
+            .unwrap();
+        assert_eq!(from_reader, Some(root));
+    }
+
+    #[test]
+    fn test_level_order_round_trip() {
+        let codec = Codec::new();
+        let values = [Some(1), Some(2), Some(3), None, None, Some(4), Some(5)];
+        let root = codec.from_level_order(&values);
+        assert_eq!(root, Some(sample_tree()));
+        assert_eq!(codec.to_level_order(root), values);
+
+        // Explicit trailing Nones build the same tree
+        let padded = [&values[..], &[None; 4]].concat();
+        assert_eq!(codec.from_level_order(&padded), Some(sample_tree()));
+
+        assert_eq!(codec.from_level_order(&[]), None);
+        assert_eq!(codec.from_level_order(&[None, Some(1)]), None);
+        assert_eq!(codec.to_level_order(None), Vec::new());
+    }
 }